@@ -1,20 +1,76 @@
-use crate::constraint::Constraints;
-use crate::linear_function::LinearFunction;
+use crate::camera::{Camera, View};
+use crate::comparison::Comparison;
+use crate::document::{self, Document};
+use crate::error::ParseError;
+use crate::explanation::Language;
+use crate::format::Format;
+use crate::linear_function::{Coefficient, Variable};
+use crate::plot::{HalfPlane, Plot2D, PlotEvent};
 use crate::polyhedron::PolyhedronRenderer;
-use crate::{Simplex, SimplexError};
+use crate::projection::Projection;
+use crate::sensitivity::WhatIf;
+use crate::tableau::Tableau;
+use crate::terminal::draw_steps;
+use crate::{LinearProgram, Simplex, SimplexError};
 use eframe::{egui_glow, Frame};
+use egui::text::LayoutJob;
 use egui::FontFamily::Proportional;
 use egui::TextStyle::{Body, Button, Heading, Monospace, Small};
-use egui::{Color32, Context, Style};
-use egui::{FontId, Sense};
+use egui::{Align2, Color32, Context, Galley, Stroke, Style, TextFormat};
+use egui::{FontId, PointerButton, Sense};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 pub struct SimplexVisualizer {
+    /// Whether the compiled objective is maximized, as the program always maximizes
     maximize: bool,
-    function_input: String,
-    constraints_input: String,
+    /// Text of the whole program, see [crate::document]
+    source: String,
+    /// Last document compiled
+    document: Option<Document>,
+    /// Constraint or bound of the document each row of the program comes from
+    row_items: Vec<usize>,
+    /// Line of the editor each constraint of the program comes from
+    constraint_lines: Vec<usize>,
+    /// Line of the editor whose constraint is under the pointer in the 3D view
+    hovered_line: Option<usize>,
+    /// Error of the last compilation, underlined in the editor
+    parse_error: Option<ParseError>,
+    /// How the editor is formatted
+    format: Format,
 
     simplex: Option<Result<Simplex, SimplexError>>,
+    optimum: Option<Vec<f32>>,
+    plot: Option<Plot2D>,
+    projection: Option<Projection>,
+    show_steps: bool,
+    /// The state is shown as a tableau rather than as a dictionary
+    show_tableau: bool,
+    /// Language of the explanation of each step
+    language: Language,
+    /// The steps are played one after the other
+    playing: bool,
+    /// Steps played per second
+    speed: f32,
+    /// Progress of the playback from the current step to the next one, between 0 and 1
+    progress: f32,
+    /// The student chooses the entering variable of each pivot
+    manual: bool,
+    /// Entering variable of the last pivot chosen by the student, and how much it improved
+    /// the objective
+    last_move: Option<(Variable, Coefficient)>,
+    /// The paths of every pivot rule are compared
+    compare: bool,
+    /// Every pivot rule run on the compiled program, computed when first compared
+    comparison: Option<Comparison>,
+    /// Sliders on the numbers of the compiled program
+    what_if: Option<WhatIf>,
+    /// The objective plane is moving towards the optimum
+    sweeping: bool,
+    /// Variables of the compiled program, identifying the problem
+    problem: String,
+    /// Camera of the 3D view of every problem compiled so far
+    cameras: HashMap<String, Camera>,
     polyhedron_renderer: Arc<Mutex<PolyhedronRenderer>>,
 }
 
@@ -22,30 +78,398 @@ impl SimplexVisualizer {
     pub fn init(cc: &eframe::CreationContext) -> SimplexVisualizer {
         SimplexVisualizer {
             maximize: true,
-            function_input: String::from("x + 6y + 13z"),
-            constraints_input: String::from(
+            source: String::from(
                 "\
-x <= 200\n\
-y <= 300\n\
-x + y + z <= 400\n\
-y + 3z <= 600\n
-            ",
+max: x + 6y + 13z\n\
+subject to\n  \
+x <= 200\n  \
+y <= 300\n  \
+x + y + z <= 400\n  \
+y + 3z <= 600\n",
             ),
+            document: None,
+            row_items: vec![],
+            constraint_lines: vec![],
+            hovered_line: None,
+            parse_error: None,
+            format: Format::default(),
 
             simplex: None,
+            optimum: None,
+            plot: None,
+            projection: None,
+            show_steps: true,
+            show_tableau: false,
+            language: Language::default(),
+            playing: false,
+            speed: 1.0,
+            progress: 0.0,
+            manual: false,
+            last_move: None,
+            compare: false,
+            comparison: None,
+            what_if: None,
+            sweeping: false,
+            problem: String::new(),
+            cameras: HashMap::new(),
             polyhedron_renderer: Arc::new(Mutex::new(
                 PolyhedronRenderer::init(cc.gl.as_ref().unwrap()).unwrap(),
             )),
         }
     }
 
+    /// Compiles the program of the editor, giving the new problem its own camera
+    fn compile(&mut self) {
+        self.solve();
+        if self.parse_error.is_some() {
+            return;
+        }
+        // Each problem keeps its own camera, problems being told apart by their variables
+        let Some(document) = &self.document else {
+            return;
+        };
+        let mut variables = document.program().0.non_gap_variables();
+        variables.sort();
+        let problem = variables.join(", ");
+        if problem == self.problem {
+            return;
+        }
+        let mut renderer = self.polyhedron_renderer.lock().unwrap();
+        let previous = std::mem::replace(&mut self.problem, problem);
+        if !previous.is_empty() {
+            self.cameras.insert(previous, renderer.camera);
+        }
+        renderer.camera = self.cameras.get(&self.problem).copied().unwrap_or_default();
+    }
+
+    /// Formats the program of the editor in place, or underlines its error
+    fn format_source(&mut self) {
+        match document::format_source(&self.source, &self.format) {
+            Ok(formatted) => {
+                self.source = formatted;
+                self.parse_error = None;
+            }
+            Err(error) => self.parse_error = Some(error),
+        }
+    }
+
+    /// Parses the program of the editor and starts the algorithm over
+    fn solve(&mut self) {
+        // Parse the document, keeping the previous program on errors
+        let (document, item_lines) = match Document::parse(&self.source) {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                self.parse_error = Some(error);
+                return;
+            }
+        };
+        self.parse_error = None;
+
+        // Create simplex
+        let (program, row_items) = document.program();
+        self.constraint_lines = row_items.iter().map(|&item| item_lines[item]).collect();
+        self.maximize = document.sense == document::Sense::Maximize;
+        self.row_items = row_items;
+        self.document = Some(document);
+        self.simplex = Some(program.constraints.maximize(&program.linear_function));
+        // The timeline covers every step, unless the student chooses them
+        if let (Some(Ok(simplex)), false) = (&mut self.simplex, self.manual) {
+            simplex.compute_all_steps(true);
+        }
+        self.last_move = None;
+        self.comparison = None;
+        self.playing = false;
+        self.optimum = match &self.simplex {
+            Some(Ok(simplex)) => simplex.optimum(true),
+            _ => None,
+        };
+        self.what_if = (self.optimum.as_ref())
+            .zip(self.document.as_ref())
+            .map(|(_, document)| WhatIf::new(document));
+        // Programs with four variables or more are projected
+        self.projection = if program.non_gap_variables().len() >= 4 {
+            Some(Projection::new(&program))
+        } else {
+            None
+        };
+        self.update_view(&program);
+    }
+
+    /// Rewrites the line of the editor of a constraint dragged in the 2D view,
+    /// and solves the program again
+    fn move_constraint(&mut self, index: usize, half_plane: HalfPlane) {
+        let (Some(&line), Some(&item), Some(plot), Some(document)) = (
+            self.constraint_lines.get(index),
+            self.row_items.get(index),
+            &self.plot,
+            &self.document,
+        ) else {
+            return;
+        };
+        // Bounds have to stay on a single variable, and a chain of comparisons on a single line
+        let chained = (self.constraint_lines.iter().zip(&self.row_items))
+            .any(|(&other_line, &other)| other_line == line && other != item);
+        let (Some(written), false) = (document.constraints.get(item), chained) else {
+            return;
+        };
+        let constraint = half_plane.as_constraint(plot.variables(), written.constraint.operator);
+        let mut lines: Vec<String> = self.source.lines().map(String::from).collect();
+        lines[line] = document::rewrite_constraint(&lines[line], &constraint);
+        let trailing_newline = self.source.ends_with('\n');
+        self.source = lines.join("\n");
+        if trailing_newline {
+            self.source.push('\n');
+        }
+        self.solve();
+    }
+
+    /// Rebuilds the 2D plot or the 3D polyhedron of the displayed variables
+    fn update_view(&mut self, program: &LinearProgram) {
+        let keeps_constraints = self
+            .projection
+            .as_ref()
+            .map_or(true, |p| p.keeps_constraints());
+        let program = match &mut self.projection {
+            Some(projection) => projection.apply(program),
+            None => program.clone(),
+        };
+
+        // Programs with two variables get a dedicated 2D view
+        if program.non_gap_variables().len() <= 2 {
+            let mut plot = Plot2D::new(&program);
+            // Projected constraints and split free variables can't be written back to the editor
+            plot.draggable = self.projection.is_none()
+                && self.document.as_ref().is_some_and(|d| d.free.is_empty());
+            self.plot = Some(plot);
+        } else {
+            self.plot = None;
+            // Constraints are labelled after their line in the editor, unless they were
+            // replaced by the projection
+            let lines: Vec<&str> = self
+                .source
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or_default())
+                .collect();
+            let labels = program
+                .constraints
+                .iter()
+                .enumerate()
+                .map(|(i, constraint)| {
+                    match self.constraint_lines.get(i).and_then(|&l| lines.get(l)) {
+                        Some(line) if keeps_constraints => line.trim().to_string(),
+                        _ => constraint.to_string(),
+                    }
+                })
+                .collect();
+            self.polyhedron_renderer
+                .lock()
+                .unwrap()
+                .set_program(&program, labels);
+        }
+    }
+
+    /// Returns the pivots the student can choose from in manual mode,
+    /// as the entering variable along with the state it leads to
+    fn candidates(&self) -> Vec<(Variable, LinearProgram)> {
+        match (&self.simplex, self.manual) {
+            (Some(Ok(simplex)), true) => {
+                let current = simplex.current_state();
+                current
+                    .out_of_base_variables()
+                    .into_iter()
+                    .filter_map(|var| current.pivoted(&var).ok().map(|next| (var, next)))
+                    .collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Performs the pivot chosen by the student, remembering how it changed the objective
+    fn choose_pivot(&mut self, var: &Variable) {
+        if let Some(Ok(simplex)) = &mut self.simplex {
+            let before = simplex.current_state().objective_value();
+            if simplex.pivot_on(var).is_ok() {
+                let gain = simplex.current_state().objective_value() - before;
+                let name = simplex.current_state().constraints.display_name(var);
+                self.last_move = Some((name, gain));
+            }
+        }
+    }
+
+    /// Position of the marker of the current point. During playback, it moves along the edge
+    /// leading to the next point
+    fn marker_point(&self, simplex: &Simplex) -> Vec<f32> {
+        let points = simplex.historic_points();
+        let current = &points[simplex.index()];
+        match points.get(simplex.index() + 1) {
+            Some(next) if self.playing => current
+                .iter()
+                .zip(next)
+                .map(|(from, to)| from + (to - from) * self.progress)
+                .collect(),
+            _ => current.clone(),
+        }
+    }
+
+    /// Moves the playback forward, going to the next step once the marker reaches it
+    fn play(&mut self, dt: f32) {
+        let Some(Ok(simplex)) = &mut self.simplex else {
+            self.playing = false;
+            return;
+        };
+        self.progress += dt * self.speed;
+        if self.progress >= 1.0 {
+            self.progress = 0.0;
+            simplex.go_to(simplex.index() + 1);
+        }
+        if simplex.index() + 1 >= simplex.historic().len() {
+            self.playing = false;
+            self.progress = 0.0;
+        }
+    }
+
+    /// Paths taken by every pivot rule when comparing them, with the name and color of the rule
+    fn comparison_paths(&self) -> Vec<(String, Color32, Vec<Vec<f32>>)> {
+        match (&self.comparison, self.compare) {
+            (Some(comparison), true) => comparison
+                .runs
+                .iter()
+                .map(|run| {
+                    let points = run
+                        .simplex
+                        .historic_points()
+                        .iter()
+                        .map(|p| self.displayed_point(p))
+                        .collect();
+                    (run.rule.to_string(), run.color, points)
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Keeps the coordinates of a point that correspond to the displayed variables
+    fn displayed_point(&self, point: &[f32]) -> Vec<f32> {
+        match &self.projection {
+            Some(projection) => projection.point(point),
+            None => point.to_vec(),
+        }
+    }
+
     fn draw_polyhedron(&mut self, ui: &mut egui::Ui) {
+        {
+            let mut renderer = self.polyhedron_renderer.lock().unwrap();
+            let (min, max) = renderer.objective_range();
+            ui.horizontal(|ui| {
+                ui.add(egui::Slider::new(&mut renderer.alpha, 0.0..=1.0).text("Opacity"));
+                ui.checkbox(&mut renderer.show_axes, "Axes");
+                ui.checkbox(&mut renderer.show_constraint_planes, "Constraints");
+                ui.checkbox(&mut renderer.show_objective_plane, "Objective");
+                if renderer.show_objective_plane {
+                    ui.add(egui::Slider::new(&mut renderer.objective_level, min..=max));
+                    if ui.add(egui::Button::new("SWEEP")).clicked() {
+                        renderer.objective_level = min;
+                        self.sweeping = true;
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let camera = &mut renderer.camera;
+                if ui.add(egui::Button::new("RESET")).clicked() {
+                    *camera = Camera::default();
+                }
+                for (view, name) in [(View::XY, "XY"), (View::XZ, "XZ"), (View::YZ, "YZ")] {
+                    if ui.selectable_label(camera.view == view, name).clicked() {
+                        *camera = Camera {
+                            view,
+                            ..Camera::default()
+                        };
+                    }
+                }
+            });
+
+            // The objective plane sweeps the polyhedron in two seconds, up to the optimum
+            if self.sweeping {
+                let dt = ui.input(|i| i.stable_dt);
+                renderer.objective_level += (max - min) * dt / 2.0;
+                if renderer.objective_level >= max {
+                    renderer.objective_level = max;
+                    self.sweeping = false;
+                }
+                ui.ctx().request_repaint();
+            }
+        }
+
         let (rect, response) =
-            ui.allocate_exact_size(ui.available_size_before_wrap(), Sense::drag());
+            ui.allocate_exact_size(ui.available_size_before_wrap(), Sense::click_and_drag());
         ui.expand_to_include_rect(rect);
 
-        // Check angle
-        self.polyhedron_renderer.lock().unwrap().view_angle += response.drag_delta() * 0.01;
+        // Drag to rotate, right-drag to pan and scroll to zoom
+        {
+            let camera = &mut self.polyhedron_renderer.lock().unwrap().camera;
+            if response.dragged_by(PointerButton::Primary) {
+                camera.rotate(response.drag_delta());
+            } else if response.dragged_by(PointerButton::Secondary) {
+                camera.pan_by(response.drag_delta(), rect.height());
+            }
+            if response.hovered() {
+                camera.zoom_by(ui.input(|i| i.scroll_delta.y));
+            }
+        }
+
+        // Highlight the facet under the pointer, and its line in the editor
+        let hovered = response.hover_pos().and_then(|pointer| {
+            self.polyhedron_renderer
+                .lock()
+                .unwrap()
+                .hovered_constraint(rect, pointer)
+        });
+        self.polyhedron_renderer
+            .lock()
+            .unwrap()
+            .set_hovered(hovered);
+        self.hovered_line = match (&self.projection, hovered) {
+            (Some(projection), _) if !projection.keeps_constraints() => None,
+            (_, Some(constraint)) => self.constraint_lines.get(constraint).copied(),
+            _ => None,
+        };
+
+        // In manual mode, clicking a neighbouring vertex pivots towards it
+        let candidates = self.candidates();
+        let candidate_points = candidates
+            .iter()
+            .map(|(_, next)| self.displayed_point(&next.point()))
+            .collect();
+        self.polyhedron_renderer
+            .lock()
+            .unwrap()
+            .set_candidates(candidate_points);
+        if let (true, Some(pointer)) = (response.clicked(), response.interact_pointer_pos()) {
+            let picked = self
+                .polyhedron_renderer
+                .lock()
+                .unwrap()
+                .candidate_at(rect, pointer);
+            if let Some(i) = picked {
+                self.choose_pivot(&candidates[i].0);
+            }
+        }
+
+        // Show the progress of the algorithm
+        let mut current_point = vec![];
+        if let Some(Ok(simplex)) = &self.simplex {
+            current_point = self.displayed_point(&self.marker_point(simplex));
+            self.polyhedron_renderer.lock().unwrap().set_path(
+                simplex
+                    .historic_points()
+                    .iter()
+                    .map(|p| self.displayed_point(p))
+                    .collect(),
+                simplex.index(),
+                self.optimum.as_ref().map(|p| self.displayed_point(p)),
+            );
+        }
         let polyhedron_renderer = self.polyhedron_renderer.clone();
 
         let callback = egui::PaintCallback {
@@ -53,17 +477,84 @@ y + 3z <= 600\n
             callback: Arc::new(egui_glow::CallbackFn::new(move |info, painter| {
                 polyhedron_renderer.lock().unwrap().draw(
                     painter.gl(),
-                    info.screen_size_px,
-                    &[0.0; 3],
+                    info.viewport.aspect_ratio(),
+                    &current_point,
                 )
             })),
         };
         ui.painter().add(callback);
+
+        for (position, label, color) in self
+            .polyhedron_renderer
+            .lock()
+            .unwrap()
+            .labels(rect, response.hover_pos())
+        {
+            ui.painter().text(
+                position,
+                Align2::CENTER_CENTER,
+                label,
+                FontId::proportional(14.0),
+                color,
+            );
+        }
+    }
+}
+
+/// Lays out the text of an editor, highlighting a line and underlining the span of an error
+fn layout_editor(
+    ui: &egui::Ui,
+    text: &str,
+    wrap_width: f32,
+    highlighted_line: Option<usize>,
+    error: Option<&ParseError>,
+) -> Arc<Galley> {
+    let font_id = Body.resolve(ui.style());
+    let mut job = LayoutJob::default();
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        let format = TextFormat {
+            font_id: font_id.clone(),
+            color: ui.visuals().text_color(),
+            background: if Some(i) == highlighted_line {
+                Color32::from_rgb(102, 92, 84)
+            } else {
+                Color32::TRANSPARENT
+            },
+            ..Default::default()
+        };
+        match error.filter(|error| error.line == i) {
+            Some(error) => {
+                let byte = |column: usize| {
+                    line.char_indices()
+                        .nth(column)
+                        .map_or(line.len(), |(byte, _)| byte)
+                };
+                let (start, end) = (byte(error.column), byte(error.column + error.length));
+                job.append(&line[..start], 0.0, format.clone());
+                job.append(
+                    &line[start..end],
+                    0.0,
+                    TextFormat {
+                        underline: Stroke::new(2.0, Color32::RED),
+                        ..format.clone()
+                    },
+                );
+                job.append(&line[end..], 0.0, format);
+            }
+            None => job.append(line, 0.0, format),
+        }
     }
+    job.wrap.max_width = wrap_width;
+    ui.fonts(|f| f.layout_job(job))
 }
 
 impl eframe::App for SimplexVisualizer {
     fn update(&mut self, ctx: &Context, _frame: &mut Frame) {
+        if self.playing {
+            self.play(ctx.input(|i| i.stable_dt));
+            ctx.request_repaint();
+        }
+
         // Change font sizes
         let mut style = (*ctx.style()).clone();
         style.text_styles = [
@@ -84,44 +575,50 @@ impl eframe::App for SimplexVisualizer {
                     .show(ui, |ui| {
                         ui.vertical(|ui| {
                             ui.heading("Linear Program");
+                            // The line of the hovered facet is highlighted
+                            let (hovered_line, error) =
+                                (self.hovered_line, self.parse_error.as_ref());
+                            let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+                                layout_editor(ui, text, wrap_width, hovered_line, error)
+                            };
+                            ui.add(
+                                egui::TextEdit::multiline(&mut self.source).layouter(&mut layouter),
+                            );
+                            if let Some(error) = &self.parse_error {
+                                ui.colored_label(Color32::RED, error.to_string());
+                            }
+                            if let Some(document) =
+                                self.document.as_ref().filter(|d| !d.integers.is_empty())
+                            {
+                                ui.colored_label(
+                                    Color32::GRAY,
+                                    format!(
+                                        "{} declared integer, the relaxation is solved",
+                                        document.integers.join(", ")
+                                    ),
+                                );
+                            }
+
                             ui.horizontal(|ui| {
-                                egui::ComboBox::from_label("")
-                                    .selected_text(
-                                        (if self.maximize { "MAX" } else { "MIN" }).to_string(),
-                                    )
-                                    .show_ui(ui, |ui| {
-                                        ui.selectable_value(&mut self.maximize, true, "MAX");
-                                        ui.selectable_value(&mut self.maximize, false, "MIN");
-                                    });
-                                ui.text_edit_singleline(&mut self.function_input);
+                                if ui.add(egui::Button::new("COMPILE")).clicked() {
+                                    self.compile();
+                                }
+                                if ui.add(egui::Button::new("FORMAT")).clicked() {
+                                    self.format_source();
+                                }
+                                let precision = &mut self.format.precision;
+                                ui.selectable_value(precision, None, "EXACT");
+                                ui.selectable_value(precision, Some(2), "0.01");
+                                ui.checkbox(&mut self.format.fractions, "Fractions");
+                                ui.checkbox(&mut self.format.align, "Align");
                             });
-                            ui.text_edit_multiline(&mut self.constraints_input);
-
-                            if ui.add(egui::Button::new("COMPILE")).clicked() {
-                                // Parse constraints
-                                let constraints =
-                                    Constraints::compile(&self.constraints_input).unwrap();
-                                // Parse linear function
-                                let function = self
-                                    .function_input
-                                    .parse()
-                                    .unwrap_or(LinearFunction::zero());
-
-                                // Create simplex
-                                self.simplex = Some(constraints.maximize(&if self.maximize {
-                                    function
-                                } else {
-                                    -function
-                                }));
-                                self.polyhedron_renderer
-                                    .lock()
-                                    .unwrap()
-                                    .polyhedron_from_constraints(&constraints);
-                            }
                         });
                     })
             });
 
+        let candidates = self.candidates();
+        let mut chosen = None;
+        let mut manual_changed = false;
         egui::Area::new("State")
             .default_pos(egui::pos2(512f32, 512f32))
             .show(ctx, |ui| {
@@ -136,13 +633,105 @@ impl eframe::App for SimplexVisualizer {
                                     format!("{acc}{v} = {c}\n")
                                 }));
 
-                                ui.heading("State");
+                                ui.horizontal(|ui| {
+                                    ui.heading("State");
+                                    ui.selectable_value(&mut self.show_tableau, false, "DICTIONARY");
+                                    ui.selectable_value(&mut self.show_tableau, true, "TABLEAU");
+                                });
                                 let current_state = simplex.current_state();
-                                ui.colored_label(
-                                    Color32::RED,
-                                    format!("max {}", current_state.linear_function),
-                                );
-                                ui.label(current_state.constraints.to_string());
+                                if self.show_tableau {
+                                    Tableau::new(current_state).draw(ui);
+                                } else {
+                                    // Gap variables of named constraints are shown as slack(name)
+                                    let named = current_state.named();
+                                    // Rounded even when the editor is exact, f32 noise aside
+                                    let format = Format {
+                                        precision: self.format.precision.or(Some(2)),
+                                        ..self.format
+                                    };
+                                    ui.colored_label(
+                                        Color32::RED,
+                                        format!("max {}", format.function(&named.linear_function)),
+                                    );
+                                    let constraints = named.constraints.iter().map(|c| {
+                                        let sides = [c.left.clone(), c.right.clone()];
+                                        format.comparison(&sides, &[c.operator]) + "\n"
+                                    });
+                                    ui.label(constraints.collect::<String>());
+                                }
+
+                                if let Some(explanation) = simplex.explanation(simplex.index()) {
+                                    ui.horizontal(|ui| {
+                                        ui.heading("Explanation");
+                                        ui.selectable_value(
+                                            &mut self.language,
+                                            Language::English,
+                                            "EN",
+                                        );
+                                        ui.selectable_value(
+                                            &mut self.language,
+                                            Language::French,
+                                            "FR",
+                                        );
+                                    });
+                                    ui.scope(|ui| {
+                                        ui.set_max_width(480.0);
+                                        ui.add(
+                                            egui::Label::new(explanation.to_prose(self.language))
+                                                .wrap(true),
+                                        );
+                                    });
+                                }
+
+                                manual_changed =
+                                    ui.checkbox(&mut self.manual, "Manual pivots").changed();
+                                if self.manual {
+                                    // Moves are shown as changes of the objective the user wrote
+                                    let sign = if self.maximize { 1.0 } else { -1.0 };
+                                    ui.horizontal_wrapped(|ui| {
+                                        for (var, next) in &candidates {
+                                            let gain = next.objective_value()
+                                                - current_state.objective_value();
+                                            let color = match gain {
+                                                gain if gain > 0.0 => Color32::GREEN,
+                                                gain if gain < 0.0 => Color32::RED,
+                                                _ => Color32::GRAY,
+                                            };
+                                            let text = egui::RichText::new(format!(
+                                                "{} ({:+.2})",
+                                                current_state.constraints.display_name(var),
+                                                sign * gain
+                                            ))
+                                            .color(color);
+                                            if ui.button(text).clicked() {
+                                                chosen = Some(var.clone());
+                                            }
+                                        }
+                                    });
+                                    match &self.last_move {
+                                        Some((var, gain)) if *gain > 0.0 => ui.colored_label(
+                                            Color32::GREEN,
+                                            format!("{var} entered: the objective improved by {gain:.2}"),
+                                        ),
+                                        Some((var, gain)) if *gain < 0.0 => ui.colored_label(
+                                            Color32::RED,
+                                            format!("{var} entered: the objective got worse by {:.2}", -gain),
+                                        ),
+                                        Some((var, _)) => ui.colored_label(
+                                            Color32::GRAY,
+                                            format!("{var} entered: the objective did not change"),
+                                        ),
+                                        None => ui.label("Click a green vertex or a variable to pivot"),
+                                    };
+                                }
+
+                                ui.checkbox(&mut self.compare, "Compare pivot rules");
+                                if self.compare {
+                                    let comparison = self.comparison.get_or_insert_with(|| {
+                                        Comparison::new(simplex.initial_state())
+                                    });
+                                    comparison.draw_table(ui);
+                                }
                             }
                             Some(Err(SimplexError::Unbounded)) => {
                                 ui.colored_label(Color32::RED, "This program is unbounded");
@@ -160,20 +749,185 @@ impl eframe::App for SimplexVisualizer {
                             if ui.add(egui::Button::new("PREVIOUS")).clicked() {
                                 if let Some(Ok(simplex)) = &mut self.simplex {
                                     simplex.previous_step();
+                                    self.last_move = None;
+                                    self.playing = false;
                                 }
                             }
-                            // Next button
-                            if ui.add(egui::Button::new("NEXT")).clicked() {
+                            // Play button, over the steps taken so far in manual mode
+                            let play = if self.playing { "PAUSE" } else { "PLAY" };
+                            if ui.add(egui::Button::new(play)).clicked() {
                                 if let Some(Ok(simplex)) = &mut self.simplex {
-                                    simplex.next_step(true);
+                                    if !self.manual {
+                                        simplex.compute_all_steps(true);
+                                    }
+                                    if simplex.index() + 1 >= simplex.historic().len() {
+                                        simplex.go_to(0);
+                                    }
+                                    self.playing = !self.playing;
+                                    self.progress = 0.0;
                                 }
                             }
-                        })
+                            // Next button, replaced by the choice of the pivot in manual mode
+                            if !self.manual && ui.add(egui::Button::new("NEXT")).clicked() {
+                                if let Some(Ok(simplex)) = &mut self.simplex {
+                                    let _ = simplex.next_step(true);
+                                    self.playing = false;
+                                }
+                            }
+                        });
+
+                        // Timeline over the steps computed, only the ones taken in manual mode
+                        if let Some(Ok(simplex)) = &mut self.simplex {
+                            ui.horizontal(|ui| {
+                                let mut step = simplex.index();
+                                let last_step = simplex.historic().len() - 1;
+                                if ui
+                                    .add(egui::Slider::new(&mut step, 0..=last_step).text("Step"))
+                                    .changed()
+                                {
+                                    simplex.go_to(step);
+                                    self.progress = 0.0;
+                                }
+                                ui.add(
+                                    egui::Slider::new(&mut self.speed, 0.25..=4.0)
+                                        .logarithmic(true)
+                                        .text("Steps per second"),
+                                );
+                            });
+                        }
                     })
             });
+        if let Some(var) = chosen {
+            self.choose_pivot(&var);
+        }
+        // Steps computed by the algorithm are hidden from the student choosing them
+        if let (true, Some(Ok(simplex))) = (manual_changed, &mut self.simplex) {
+            match self.manual {
+                true => simplex.forget_next_steps(),
+                false => simplex.compute_all_steps(true),
+            }
+            self.playing = false;
+        }
+
+        // Moving a slider re-solves the program from the previous optimal basis, whose path
+        // is then shown
+        if let Some(what_if) = &mut self.what_if {
+            let mut changed = false;
+            egui::Area::new("What if")
+                .default_pos(egui::pos2(1024f32, 512f32))
+                .show(ctx, |ui| {
+                    egui::Frame::window(&Style::default())
+                        .fill(Color32::BLACK)
+                        .show(ui, |ui| {
+                            ui.heading("What if");
+                            changed = what_if.ui(ui);
+                        })
+                });
+            if changed {
+                let program = what_if.program();
+                self.optimum = what_if.optimum();
+                self.simplex = Some(match what_if.simplex() {
+                    Some(simplex) => Ok(simplex.clone()),
+                    None => program.constraints.maximize(&program.linear_function),
+                });
+                // The student pivots from the warm start
+                if let (Some(Ok(simplex)), true) = (&mut self.simplex, self.manual) {
+                    simplex.go_to(0);
+                    simplex.forget_next_steps();
+                }
+                self.comparison = None;
+                self.last_move = None;
+                self.playing = false;
+                self.update_view(&program);
+            }
+        }
+
+        if let (Some(Ok(simplex)), Some(projection)) = (&self.simplex, &mut self.projection) {
+            let mut changed = false;
+            egui::Area::new("View")
+                .default_pos(egui::pos2(32f32, 128f32))
+                .show(ctx, |ui| {
+                    egui::Frame::window(&Style::default())
+                        .fill(Color32::BLACK)
+                        .show(ui, |ui| {
+                            ui.heading("View");
+                            ui.horizontal(|ui| {
+                                ui.selectable_value(&mut self.show_steps, true, "STEPS");
+                                ui.selectable_value(&mut self.show_steps, false, "PROJECTION");
+                            });
+                            if !self.show_steps {
+                                changed = projection.ui(ui);
+                            }
+                        })
+                });
+            if changed {
+                let program = simplex.initial_state().clone();
+                self.update_view(&program);
+            }
+        }
 
-        if self.simplex.is_some() {
-            egui::CentralPanel::default().show(ctx, |ui| self.draw_polyhedron(ui));
+        let comparison_paths = self.comparison_paths();
+        if let Some(plot) = &mut self.plot {
+            plot.set_comparison_paths(comparison_paths.clone());
+        }
+        self.polyhedron_renderer
+            .lock()
+            .unwrap()
+            .set_comparison_paths(
+                comparison_paths
+                    .into_iter()
+                    .map(|(_, color, points)| (color, points))
+                    .collect(),
+            );
+
+        let mut event = None;
+        match (&self.simplex, &self.plot) {
+            // Programs with four variables or more are printed step by step by default
+            (Some(Ok(simplex)), _) if self.projection.is_some() && self.show_steps => {
+                egui::CentralPanel::default().show(ctx, |ui| draw_steps(ui, simplex));
+            }
+            (Some(Ok(simplex)), Some(plot)) => {
+                let points: Vec<Vec<f32>> = simplex
+                    .historic_points()
+                    .iter()
+                    .map(|p| self.displayed_point(p))
+                    .collect();
+                let optimum = self.optimum.as_ref().map(|p| self.displayed_point(p));
+                let marker = self.displayed_point(&self.marker_point(simplex));
+                let candidates = self.candidates();
+                let candidate_points: Vec<Vec<f32>> = candidates
+                    .iter()
+                    .map(|(_, next)| self.displayed_point(&next.point()))
+                    .collect();
+                event = egui::CentralPanel::default()
+                    .show(ctx, |ui| {
+                        plot.draw(
+                            ui,
+                            &points,
+                            simplex.index(),
+                            &marker,
+                            optimum.as_ref(),
+                            &candidate_points,
+                        )
+                    })
+                    .inner;
+                if let Some(PlotEvent::CandidateClicked(i)) = event {
+                    self.choose_pivot(&candidates[i].0);
+                }
+            }
+            // The region is still drawn, so that a constraint dragged too far can be brought back
+            (Some(Err(_)), Some(plot)) => {
+                event = egui::CentralPanel::default()
+                    .show(ctx, |ui| plot.draw(ui, &[], 0, &[0.0, 0.0], None, &[]))
+                    .inner;
+            }
+            (Some(_), _) => {
+                egui::CentralPanel::default().show(ctx, |ui| self.draw_polyhedron(ui));
+            }
+            _ => {}
+        }
+        if let Some(PlotEvent::ConstraintMoved(index, half_plane)) = event {
+            self.move_constraint(index, half_plane);
         }
     }
 }
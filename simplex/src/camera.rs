@@ -0,0 +1,189 @@
+//! Camera of the 3D view, which can be rotated, zoomed and panned,
+//! or snapped to an orthographic view along an axis
+use std::f32::consts::FRAC_PI_4;
+
+use egui::Vec2;
+use glm::{Matrix4, Vector3, Vector4};
+use num_traits::identities::One;
+
+/// Half of the height seen by orthographic views, the polyhedron fitting in [-1, 1]
+const ORTHO_HALF_HEIGHT: f32 = 1.5;
+const FIELD_OF_VIEW: f32 = FRAC_PI_4;
+
+/// How the polyhedron is looked at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum View {
+    /// Perspective view, rotated by dragging
+    #[default]
+    Free,
+    /// Orthographic view of the plane of the first two variables
+    XY,
+    /// Orthographic view of the plane of the first and third variables
+    XZ,
+    /// Orthographic view of the plane of the second and third variables
+    YZ,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub view: View,
+    /// Rotation of the free view around the vertical axis, then the horizontal one
+    pub angle: Vec2,
+    pub zoom: f32,
+    /// Translation of the view, in the units of the normalized polyhedron
+    pub pan: Vec2,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            view: View::Free,
+            angle: Vec2::ZERO,
+            zoom: 1.0,
+            pan: Vec2::ZERO,
+        }
+    }
+}
+
+/// Builds a matrix from its rows
+fn from_rows(rows: [[f32; 4]; 4]) -> Matrix4<f32> {
+    let column = |c: usize| Vector4::new(rows[0][c], rows[1][c], rows[2][c], rows[3][c]);
+    Matrix4::new(column(0), column(1), column(2), column(3))
+}
+
+impl Camera {
+    /// Rotates the camera by a drag on the screen, leaving orthographic views
+    pub fn rotate(&mut self, delta: Vec2) {
+        self.view = View::Free;
+        self.angle += delta * 0.01;
+    }
+
+    /// Zooms in when scrolling up, and out when scrolling down
+    pub fn zoom_by(&mut self, scroll: f32) {
+        self.zoom = (self.zoom * (scroll * 0.002).exp()).clamp(0.1, 20.0);
+    }
+
+    /// Moves the view along with a drag on the screen, on a view of the given height in pixels
+    pub fn pan_by(&mut self, delta: Vec2, height: f32) {
+        let units_per_pixel = self.visible_height() / height;
+        self.pan += Vec2::new(delta.x, -delta.y) * units_per_pixel;
+    }
+
+    /// Height seen by the camera around the center of the polyhedron
+    fn visible_height(&self) -> f32 {
+        match self.view {
+            View::Free => {
+                let distance = (0.5f32 * 0.5 + 2.0 * 2.0).sqrt() / self.zoom;
+                2.0 * distance * (FIELD_OF_VIEW / 2.0).tan()
+            }
+            _ => 2.0 * ORTHO_HALF_HEIGHT / self.zoom,
+        }
+    }
+
+    /// Returns the model-view-projection matrix and the model matrix
+    pub fn transform(&self, aspect_ratio: f32) -> (Matrix4<f32>, Matrix4<f32>) {
+        let pan = glm::ext::translate(&Matrix4::one(), Vector3::new(self.pan.x, self.pan.y, 0.0));
+        let (projection, view, model) = match self.view {
+            View::Free => {
+                let projection = glm::ext::perspective(FIELD_OF_VIEW, aspect_ratio, 0.01, 100.0);
+                let view = glm::ext::look_at(
+                    Vector3::new(0.0, 0.5, 2.0) / self.zoom,
+                    Vector3::new(0.0, 0.0, -0.01),
+                    Vector3::new(0.0, 1.0, 0.0),
+                );
+                let model = glm::ext::rotate(
+                    &glm::ext::rotate(&Matrix4::one(), self.angle.x, Vector3::new(0.0, 1.0, 0.0)),
+                    self.angle.y,
+                    Vector3::new(1.0, 0.0, 0.0),
+                );
+                (projection, view, model)
+            }
+            ortho => {
+                let (half_width, half_height) = (
+                    ORTHO_HALF_HEIGHT * aspect_ratio / self.zoom,
+                    ORTHO_HALF_HEIGHT / self.zoom,
+                );
+                let (near, far) = (0.01, 100.0);
+                let projection = from_rows([
+                    [1.0 / half_width, 0.0, 0.0, 0.0],
+                    [0.0, 1.0 / half_height, 0.0, 0.0],
+                    [0.0, 0.0, -2.0 / (far - near), -(far + near) / (far - near)],
+                    [0.0, 0.0, 0.0, 1.0],
+                ]);
+                let view = glm::ext::look_at(
+                    Vector3::new(0.0, 0.0, 3.0),
+                    Vector3::new(0.0, 0.0, 0.0),
+                    Vector3::new(0.0, 1.0, 0.0),
+                );
+                // Sends the two axes of the plane to the horizontal and vertical axes of the screen
+                let model = from_rows(match ortho {
+                    View::XZ => [
+                        [1.0, 0.0, 0.0, 0.0],
+                        [0.0, 0.0, 1.0, 0.0],
+                        [0.0, -1.0, 0.0, 0.0],
+                        [0.0, 0.0, 0.0, 1.0],
+                    ],
+                    View::YZ => [
+                        [0.0, 1.0, 0.0, 0.0],
+                        [0.0, 0.0, 1.0, 0.0],
+                        [1.0, 0.0, 0.0, 0.0],
+                        [0.0, 0.0, 0.0, 1.0],
+                    ],
+                    _ => [
+                        [1.0, 0.0, 0.0, 0.0],
+                        [0.0, 1.0, 0.0, 0.0],
+                        [0.0, 0.0, 1.0, 0.0],
+                        [0.0, 0.0, 0.0, 1.0],
+                    ],
+                });
+                (projection, view, model)
+            }
+        };
+        (projection * pan * view * model, model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Position of a point in normalized device coordinates
+    fn project(camera: &Camera, [x, y, z]: [f32; 3]) -> [f32; 2] {
+        let p = camera.transform(1.0).0 * Vector4::new(x, y, z, 1.0);
+        [p.x / p.w, p.y / p.w]
+    }
+
+    #[test]
+    fn test_ortho_views() {
+        let mut camera = Camera {
+            view: View::XZ,
+            ..Camera::default()
+        };
+        // The third axis goes up the screen, the second one goes away from it
+        let [x, y] = project(&camera, [0.0, 0.0, 1.0]);
+        assert!(x.abs() < 1e-6 && y > 0.0);
+        let [x, y] = project(&camera, [0.0, 1.0, 0.0]);
+        assert!(x.abs() < 1e-6 && y.abs() < 1e-6);
+
+        camera.view = View::YZ;
+        let [x, y] = project(&camera, [0.0, 1.0, 0.0]);
+        assert!(x > 0.0 && y.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_zoom_and_pan() {
+        let mut camera = Camera {
+            view: View::XY,
+            ..Camera::default()
+        };
+        let [x, _] = project(&camera, [1.0, 0.0, 0.0]);
+        camera.zoom_by(500.0);
+        let [zoomed_x, _] = project(&camera, [1.0, 0.0, 0.0]);
+        assert!(zoomed_x > x);
+
+        // Dragging across the whole view moves the center to the edge of the screen
+        camera.pan_by(Vec2::new(0.0, -100.0), 200.0);
+        let [_, y] = project(&camera, [0.0, 0.0, 0.0]);
+        assert!((y - 1.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,171 @@
+//! Comparison of the paths taken by different pivot rules on the same program
+use crate::linear_function::Coefficient;
+use crate::{LinearProgram, PivotRule, Simplex, SimplexError};
+use egui::{Color32, RichText, Ui};
+use itertools::Itertools;
+
+/// Color of the path of each pivot rule, in the order of `PivotRule::ALL`
+const RULE_COLORS: [Color32; 3] = [
+    Color32::from_rgb(131, 165, 152),
+    Color32::from_rgb(211, 134, 155),
+    Color32::from_rgb(184, 187, 38),
+];
+
+/// Largest number of pivots made with a rule before it is taken as cycling
+const MAX_PIVOTS: usize = 500;
+
+/// How the algorithm stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Optimal,
+    /// The program turned out to be unbounded along the way
+    Unbounded,
+    /// A basis came back, or the rule made too many pivots, so the run was stopped
+    Cycled,
+    /// The rule chose a variable that can't enter the basis
+    InvalidPivot,
+}
+
+/// The algorithm run to the end with a pivot rule
+pub struct Run {
+    pub rule: PivotRule,
+    pub color: Color32,
+    pub simplex: Simplex,
+    pub outcome: Outcome,
+}
+
+impl Run {
+    /// Number of pivots made
+    pub fn iterations(&self) -> usize {
+        self.simplex.historic().len() - 1
+    }
+
+    /// Number of pivots that didn't move the current point
+    pub fn degenerate_steps(&self) -> usize {
+        let points = self.simplex.historic_points();
+        points.windows(2).filter(|step| step[0] == step[1]).count()
+    }
+
+    pub fn objective_value(&self) -> Coefficient {
+        self.simplex.historic().last().unwrap().objective_value()
+    }
+}
+
+pub struct Comparison {
+    pub runs: Vec<Run>,
+}
+
+impl Comparison {
+    /// Runs the algorithm with every pivot rule, starting from the same state
+    pub fn new(program: &LinearProgram) -> Comparison {
+        let runs = PivotRule::ALL
+            .into_iter()
+            .zip(RULE_COLORS)
+            .map(|(rule, color)| {
+                let mut simplex = Simplex::from(program.clone());
+                let mut bases = vec![program.basis().into_iter().sorted().collect_vec()];
+                let outcome = loop {
+                    match simplex.next_step_with_rule(rule) {
+                        Ok(_) => {}
+                        Err(SimplexError::AlreadyOptimal) => break Outcome::Optimal,
+                        Err(SimplexError::Unbounded) => break Outcome::Unbounded,
+                        Err(SimplexError::InvalidPivot) => break Outcome::InvalidPivot,
+                    }
+                    let basis = simplex
+                        .current_state()
+                        .basis()
+                        .into_iter()
+                        .sorted()
+                        .collect_vec();
+                    if bases.contains(&basis) || bases.len() > MAX_PIVOTS {
+                        break Outcome::Cycled;
+                    }
+                    bases.push(basis);
+                };
+                Run {
+                    rule,
+                    color,
+                    simplex,
+                    outcome,
+                }
+            })
+            .collect();
+        Comparison { runs }
+    }
+
+    /// Draws a table comparing the runs, each rule written in the color of its path
+    pub fn draw_table(&self, ui: &mut Ui) {
+        egui::Grid::new("comparison")
+            .striped(true)
+            .spacing([16.0, 4.0])
+            .show(ui, |ui| {
+                ui.label("rule");
+                ui.label("iterations");
+                ui.label("degenerate");
+                ui.label("objective");
+                ui.end_row();
+
+                for run in &self.runs {
+                    ui.label(RichText::new(run.rule.to_string()).color(run.color));
+                    ui.label(run.iterations().to_string());
+                    ui.label(run.degenerate_steps().to_string());
+                    match run.outcome {
+                        Outcome::Optimal => ui.label(format!("{:.2}", run.objective_value())),
+                        Outcome::Unbounded => ui.label("unbounded"),
+                        Outcome::Cycled => ui.label("cycled"),
+                        Outcome::InvalidPivot => ui.label("invalid pivot"),
+                    };
+                    ui.end_row();
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::Constraints;
+
+    #[test]
+    fn test_comparison() {
+        let lp = LinearProgram {
+            linear_function: "x + 6y + 13z".parse().unwrap(),
+            constraints: Constraints::compile(
+                "x <= 200\n y <= 300\n x + y + z <= 400\n y + 3z <= 600",
+            )
+            .unwrap(),
+        };
+        let comparison = Comparison::new(&lp);
+
+        assert_eq!(comparison.runs.len(), 3);
+        for run in &comparison.runs {
+            assert_eq!(run.outcome, Outcome::Optimal);
+            assert_eq!(run.objective_value(), 3100.0);
+        }
+        // Dantzig's rule goes straight for z, Bland's rule starts with x
+        let [bland, dantzig, _] = &comparison.runs[..] else {
+            unreachable!()
+        };
+        assert!(dantzig.iterations() < bland.iterations());
+    }
+
+    #[test]
+    fn test_cycling() {
+        // Chvátal's example, on which Dantzig's rule cycles and Bland's rule doesn't.
+        // Its first two rows are swapped, since ties between leaving rows go to the last one
+        let lp = LinearProgram {
+            linear_function: "10a - 57b - 9c - 24d".parse().unwrap(),
+            constraints: Constraints::compile(
+                "0.5a - 1.5b - 0.5c + d <= 0\n 0.5a - 5.5b - 2.5c + 9d <= 0\n a <= 1",
+            )
+            .unwrap(),
+        };
+        let comparison = Comparison::new(&lp);
+        let [bland, dantzig, _] = &comparison.runs[..] else {
+            unreachable!()
+        };
+        assert_eq!(bland.outcome, Outcome::Optimal);
+        assert_eq!(bland.objective_value(), 1.0);
+        assert_eq!(dantzig.outcome, Outcome::Cycled);
+    }
+}
@@ -1,17 +1,36 @@
 //! contraintes linéaire
 use crate::linear_function::LinearFunction;
-use crate::linear_function::Variable;
+use crate::linear_function::{Coefficient, Variable};
 use crate::linear_function::GAP_VARIABLE_IDENTIFIER;
+use crate::hull::Polyhedron;
+use crate::error::ParseError;
+use crate::expression::{Context, Parser};
 use crate::{LinearProgram, Simplex, SimplexError};
 use itertools::Itertools;
-use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::character::complete::anychar;
-use nom::multi::many_till;
 use std::collections::{HashMap, HashSet, VecDeque};
 
 // Variable globale
 
+/// Comparison operators, as written in constraints
+const OPERATORS: [&str; 5] = ["<=", ">=", "=", "<", ">"];
+
+/// Every spelling of the operators, longest first so that `<=` isn't read as `<`
+const SPELLINGS: [(&str, Operator); 9] = [
+    ("<=", Operator::LessEqual),
+    (">=", Operator::GreaterEqual),
+    ("=<", Operator::LessEqual),
+    ("==", Operator::Equal),
+    ("≤", Operator::LessEqual),
+    ("≥", Operator::GreaterEqual),
+    ("<", Operator::Less),
+    (">", Operator::Greater),
+    ("=", Operator::Equal),
+];
+
+/// Largest number of hyperplane intersections tried when enumerating vertices, as their number
+/// grows exponentially with the dimension and this runs whenever the view is rebuilt
+const MAX_INTERSECTIONS: usize = 50_000;
+
 #[derive(Debug, Clone, Default, PartialEq, Copy)]
 pub enum Operator {
     #[default]
@@ -34,6 +53,9 @@ pub struct Constraint {
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct Constraints {
     inner: Vec<Constraint>,
+    /// Names given by the user to the rows, by their gap variable. They are kept through the
+    /// pivots, as gap variables keep their meaning
+    names: HashMap<Variable, String>,
 }
 
 impl Operator {
@@ -93,6 +115,77 @@ impl Constraint {
         self.left.is_one_normalized_var() && self.operator == Operator::Equal
     }
 
+    /// Parses a comparison into one constraint per operator, so that the chain
+    /// `0 <= x + y <= 10` gives both `0 <= x + y` and `x + y <= 10`
+    /// ```rust
+    /// use simplex::constraint::{Constraint, Operator};
+    /// let constraints = Constraint::parse_chain("0 ≤ x + y =< 10").unwrap();
+    /// assert_eq!(constraints.len(), 2);
+    /// assert_eq!(constraints[1].to_string(), "x + y <= 10");
+    ///
+    /// let error = Constraint::parse_chain("0 <= x >= 10").unwrap_err();
+    /// assert_eq!((error.column, error.length), (7, 2));
+    /// ```
+    pub fn parse_chain(s: &str) -> Result<Vec<Constraint>, ParseError> {
+        Constraint::parse_chain_with(s, &Context::default())
+    }
+
+    /// Parses a comparison, possibly chained, with parameters and indices
+    pub(crate) fn parse_chain_with(s: &str, context: &Context) -> Result<Vec<Constraint>, ParseError> {
+        // Byte span of each operator
+        let mut operators: Vec<(usize, usize, Operator)> = vec![];
+        let mut chars = s.char_indices();
+        while let Some((i, _)) = chars.next() {
+            if let Some((spelling, operator)) = SPELLINGS.iter().find(|(sp, _)| s[i..].starts_with(sp)) {
+                operators.push((i, i + spelling.len(), *operator));
+                // The rest of the spelling is skipped
+                chars.by_ref().take(spelling.chars().count() - 1).for_each(drop);
+            }
+        }
+        if operators.is_empty() {
+            // The whole line is underlined, as the operator could be anywhere
+            return Err(ParseError {
+                line: 0,
+                column: 0,
+                length: s.chars().count(),
+                expected: OPERATORS.iter().map(|op| op.to_string()).collect(),
+                message: "missing comparison operator".to_string(),
+            });
+        }
+
+        // A chain goes in a single direction, equalities aside
+        let direction = |operator: Operator| match operator {
+            Operator::Less | Operator::LessEqual => Some(true),
+            Operator::Greater | Operator::GreaterEqual => Some(false),
+            Operator::Equal => None,
+        };
+        let mut directions = operators.iter().filter(|(_, _, op)| direction(*op).is_some());
+        if let Some(&(_, _, first)) = directions.next() {
+            if let Some(&(start, end, _)) = directions.find(|(_, _, op)| direction(*op) != direction(first)) {
+                return Err(ParseError {
+                    line: 0,
+                    column: s[..start].chars().count(),
+                    length: s[start..end].chars().count(),
+                    expected: vec![],
+                    message: "a chain of comparisons can't change direction".to_string(),
+                });
+            }
+        }
+
+        let mut sides = vec![];
+        let mut start = 0;
+        for &(end, next, _) in operators.iter().chain([&(s.len(), s.len(), Operator::Equal)]) {
+            let side = Parser::parse_with(&s[start..end], context.clone());
+            sides.push(side.map_err(|e| e.after(&s[..start]))?);
+            start = next;
+        }
+        Ok(operators
+            .iter()
+            .zip(sides.iter().tuple_windows())
+            .map(|(&(_, _, operator), (left, right))| Constraint::new(left.clone(), operator, right.clone()))
+            .collect())
+    }
+
     pub fn non_gap_variables(&self) -> Vec<Variable> {
         let mut var_set: HashSet<Variable> = HashSet::from_iter(self.right.non_gap_variables());
         for var in self.left.non_gap_variables() {
@@ -105,7 +198,7 @@ impl Constraint {
 impl Constraints {
     /// Create a new vector of constraints
     pub fn new() -> Constraints {
-        Constraints { inner: Vec::new() }
+        Constraints { inner: Vec::new(), names: HashMap::new() }
     }
 
     pub fn maximize(&self, to_maximize: &LinearFunction) -> Result<Simplex, SimplexError> {
@@ -177,33 +270,99 @@ impl Constraints {
                 self.inner.push(constraint);
             }
             Operator::Equal => {
-                let constraint1 = Constraint {
-                    left: next_gap_var(),
-                    operator: Operator::Equal,
-                    right: right.clone() - left.clone(),
-                };
-                let constraint2 = Constraint {
-                    left: next_gap_var(),
-                    operator: Operator::Equal,
-                    right: right - left,
-                };
-                self.inner.push(constraint1);
-                self.inner.push(constraint2);
+                // left = right is both left <= right and left >= right
+                self.add_constraint(Constraint::new(left.clone(), Operator::LessEqual, right.clone()));
+                self.add_constraint(Constraint::new(left, Operator::GreaterEqual, right));
             }
         }
     }
 
+    /// Add a constraint named by the user, whose gap variable takes its name.
+    /// The two rows of an equality are told apart by the direction of their inequality
+    /// ```rust
+    /// use simplex::constraint::Constraints;
+    /// let mut constraints = Constraints::new();
+    /// constraints.add_named_constraint("x + y <= 4".parse().unwrap(), Some("capacity"));
+    /// constraints.add_named_constraint("x - y = 1".parse().unwrap(), Some("balance"));
+    /// assert_eq!(constraints.display_name(&"ε0".to_string()), "slack(capacity)");
+    /// assert_eq!(constraints.display_name(&"ε2".to_string()), "slack(balance≥)");
+    /// assert_eq!(constraints.display_name(&"x".to_string()), "x");
+    /// ```
+    pub fn add_named_constraint(&mut self, constraint: Constraint, name: Option<&str>) {
+        let first_row = self.gap_variables_count();
+        let equality = constraint.operator == Operator::Equal;
+        self.add_constraint(constraint);
+        let Some(name) = name else {
+            return;
+        };
+        for row in first_row..self.gap_variables_count() {
+            let name = match (equality, row == first_row) {
+                (false, _) => name.to_string(),
+                (true, true) => format!("{name}≤"),
+                (true, false) => format!("{name}≥"),
+            };
+            self.names.insert(format!("{GAP_VARIABLE_IDENTIFIER}{row}"), name);
+        }
+    }
+
+    /// Returns the name given by the user to the row of a gap variable
+    pub fn name(&self, var: &Variable) -> Option<&String> {
+        self.names.get(var)
+    }
+
+    /// Returns the name under which a variable is displayed, `slack(name)` for the gap variable
+    /// of a named row
+    pub fn display_name(&self, var: &Variable) -> String {
+        match self.names.get(var) {
+            Some(name) => format!("slack({name})"),
+            None => var.clone(),
+        }
+    }
+
+    /// Returns a copy where gap variables are displayed under the name of their row
+    pub fn named(&self) -> Constraints {
+        let name = |var: &Variable| self.display_name(var);
+        let inner = self.inner
+            .iter()
+            .map(|c| Constraint::new(c.left.renamed(name), c.operator, c.right.renamed(name)))
+            .collect();
+        Constraints { inner, names: HashMap::new() }
+    }
+
     pub fn gap_variables_count(&self) -> usize {
         self.inner.len()
     }
 
     // parse a string into a Constraints
-    pub fn compile(s: &str) -> Result<Self, ()> {
+    pub fn compile(s: &str) -> Result<Self, ParseError> {
+        Constraints::compile_with_lines(s).map(|(constraints, _)| constraints)
+    }
+
+    /// Parses a string into a Constraints, along with the index of the line each constraint
+    /// comes from. Equalities and chained comparisons give several constraints on the same line
+    /// ```rust
+    /// use simplex::constraint::Constraints;
+    /// let (constraints, lines) = Constraints::compile_with_lines("x <= 2\n\ny = 3").unwrap();
+    /// assert_eq!(constraints.gap_variables_count(), 3);
+    /// assert_eq!(lines, vec![0, 2, 2]);
+    ///
+    /// let error = Constraints::compile_with_lines("x <= 2\n\ny + <= 3").unwrap_err();
+    /// assert_eq!((error.line, error.column), (2, 2));
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "line 3, column 3: unexpected end of expression, expected a number, a variable or `(`"
+    /// );
+    /// ```
+    pub fn compile_with_lines(s: &str) -> Result<(Self, Vec<usize>), ParseError> {
         let mut constraints = Constraints::default();
-        for line in s.lines().filter(|l| !l.trim().is_empty()) {
-            constraints.add_constraint(line.parse::<Constraint>()?);
+        let mut lines = vec![];
+        for (i, line) in s.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            for constraint in Constraint::parse_chain(line).map_err(|e| e.on_line(i))? {
+                constraints.add_constraint(constraint);
+            }
+            lines.resize(constraints.gap_variables_count(), i);
         }
-        Ok(constraints)
+        Ok((constraints, lines))
     }
 
     /// Normalizes all constraints with respect to a variable
@@ -226,6 +385,20 @@ impl Constraints {
             .map(|(i, _)| i)
     }
 
+    /// Returns, for each constraint, how much 'var' can increase before the constraint's
+    /// left variable reaches 0 (ratio test). None if the constraint does not restrict 'var'
+    pub fn ratios(&self, var: &Variable) -> Vec<Option<Coefficient>> {
+        self.iter()
+            .map(|c| {
+                if c.right.contains(var) && c.right[var] <= 0.0 {
+                    Some(c.right.constant / -c.right[var])
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
     /// Performs a pivot step on a particular constraint with respect to a specific variable
     pub fn pivot(&mut self, constraint_index: usize, var: &Variable) {
         // Pivot the particular constraint we've targeted
@@ -265,14 +438,67 @@ impl Constraints {
         }
     }
 
-    /// Returns a list of vertices that can be used directly to render the polyhedron
-    /// corresponding to the constraints in OpenGL
-    /// This polyhedron is centered around (0, 0, 0)
-    pub fn polyhedron(&self) -> Vec<[f32; 3]> {
-        let mut polyhedron = vec![];
-        let mut max_value = 0f32;
-        let mut center = [0f32; 3];
+    /// Returns the constraints where 'var' is replaced by a fixed value,
+    /// which is a slice of the polyhedron
+    pub fn fix(&self, var: &Variable, value: Coefficient) -> Constraints {
+        let mut constraints = self.clone();
+        constraints.replace_variable_with(var, &LinearFunction::new(value, HashMap::new()));
+        constraints
+    }
+
+    /// Returns the projection of the constraints along 'var', using Fourier–Motzkin elimination.
+    /// Only works on constraints in their initial form, where no gap variable is out of base
+    pub fn eliminate(&self, var: &Variable) -> Constraints {
+        // Every constraint reads 0 <= right, and so does the variable itself
+        let rows = self
+            .iter()
+            .map(|c| c.right.clone())
+            .chain([LinearFunction::single_variable(var.to_string())]);
+
+        // Bounds the variable from below and from above, once normalized with respect to it
+        let (mut lower, mut upper, mut kept) = (vec![], vec![], vec![]);
+        for row in rows {
+            match row[var] {
+                coeff if coeff > 0.0 => lower.push(row / coeff),
+                coeff if coeff < 0.0 => upper.push(row / -coeff),
+                _ => kept.push(row),
+            }
+        }
+        for l in &lower {
+            for u in &upper {
+                kept.push(l.clone() + u.clone());
+            }
+        }
 
+        let mut constraints = Constraints::new();
+        for mut row in kept {
+            row.replace(var, &LinearFunction::zero());
+            // Rows without any variable are always satisfied when the program is feasible
+            if row.var_iter().next().is_none() && row.constant >= 0.0 {
+                continue;
+            }
+            constraints.add_constraint(Constraint::new(
+                LinearFunction::zero(),
+                Operator::LessEqual,
+                row,
+            ));
+        }
+        constraints
+    }
+
+    /// Returns the vertices of the polyhedron corresponding to the constraints,
+    /// with coordinates given in the alphabetical order of the variables
+    pub fn vertices(&self) -> Vec<Vec<f32>> {
+        if self.iter().all(|c| c.right.constant >= 0.0) {
+            self.accessible_vertices()
+        } else {
+            // The origin isn't feasible, so there is no dictionary to start the BFS from
+            self.enumerate_vertices()
+        }
+    }
+
+    /// Finds the vertices by thinking of the simplex algorithm as a graph, starting from the origin
+    fn accessible_vertices(&self) -> Vec<Vec<f32>> {
         let variables = self.non_gap_variables();
         let dummy_program = LinearProgram {
             linear_function: LinearFunction::new(
@@ -284,73 +510,118 @@ impl Constraints {
 
         // Do a BFS on the dummy simplex instance
         let mut queue = VecDeque::from([dummy_program]);
-        let mut seen = vec![];
-        let points_nearly_equal = |a: &Vec<f32>, b: &Vec<f32>| {
-            for (a, b) in a.iter().zip(b) {
-                if (a - b).abs() <= 0.000001 { return true }
-            }
-            false
-        };
+        let mut seen: Vec<Vec<f32>> = vec![];
         while let Some(program) = queue.pop_back() {
             let current_point = program.point();
-            seen.push(current_point.clone());
+            if seen.iter().any(|p| points_nearly_equal(p, &current_point)) {
+                continue;
+            }
+            seen.push(current_point);
 
-            // Use each variable as a pivot one after the other, and collect each resulting point
-            let mut adjacent_points = vec![];
+            // Use each variable as a pivot one after the other, and push each resulting point
             for var in program.out_of_base_variables() {
                 let mut p = program.clone();
                 if p.pivot(var).is_err() {
                     continue;
                 }
-                let point = p.point();
 
                 // If the point hasn't been visited before, we push it onto the stack
-                if !seen.contains(&point) {
+                if !seen.iter().any(|s| points_nearly_equal(s, &p.point())) {
                     queue.push_front(p);
                 }
-                adjacent_points.push(point);
             }
+        }
+        seen
+    }
+
+    /// Finds the vertices by intersecting every combination of hyperplanes,
+    /// keeping the intersections that satisfy every constraint.
+    /// Gives up after `MAX_INTERSECTIONS` combinations, which may leave out some vertices
+    fn enumerate_vertices(&self) -> Vec<Vec<f32>> {
+        let variables: Vec<Variable> = self.non_gap_variables().into_iter().sorted().collect();
+        let dimension = variables.len();
+
+        // Hyperplanes are given as (coefficients, constant), on which a.x + c = 0
+        let hyperplane = |f: &LinearFunction| -> (Vec<f64>, f64) {
+            (
+                variables.iter().map(|v| f[v] as f64).collect(),
+                f.constant as f64,
+            )
+        };
+        let hyperplanes: Vec<(Vec<f64>, f64)> = self
+            .iter()
+            .map(|c| hyperplane(&c.right))
+            .chain(
+                variables
+                    .iter()
+                    .map(|v| hyperplane(&LinearFunction::single_variable(v.to_string()))),
+            )
+            .collect();
+
+        let mut vertices: Vec<Vec<f32>> = vec![];
+        for combination in hyperplanes.iter().combinations(dimension).take(MAX_INTERSECTIONS) {
+            let Some(point) = solve_linear_system(
+                combination.iter().map(|(a, _)| a.clone()).collect(),
+                combination.iter().map(|(_, c)| -c).collect(),
+            ) else {
+                continue;
+            };
+            let feasible = hyperplanes.iter().all(|(a, c)| {
+                a.iter().zip(&point).map(|(a, x)| a * x).sum::<f64>() + c >= -1e-6
+            });
+            let point: Vec<f32> = point.into_iter().map(|x| x as f32).collect();
+            if feasible && !vertices.iter().any(|v| points_nearly_equal(v, &point)) {
+                vertices.push(point);
+            }
+        }
+        vertices
+    }
 
-            // Useful calculations for centering/scaling
-            let vec_to_3d_point = |p: &Vec<f32>| {
+    /// Returns the polyhedron corresponding to the constraints, ready to be rendered in OpenGL
+    /// This polyhedron is centered around (0, 0, 0)
+    pub fn polyhedron(&self) -> Polyhedron {
+        // Compute the convex hull of the vertices
+        let vertices: Vec<[f32; 3]> = self
+            .vertices()
+            .iter()
+            .map(|p| {
                 let mut point = [0.0; 3];
                 for (v, dest) in p.iter().take(3).zip(point.iter_mut()) {
                     *dest = *v
                 }
                 point
-            };
-            let current_point_3d = vec_to_3d_point(&current_point);
-
-            for (i, v) in current_point_3d.iter().enumerate() {
-                center[i] += *v;
-                if v.abs() >= max_value {
-                    max_value = v.abs();
-                }
-            }
+            })
+            .collect();
+        Polyhedron::from_points(&vertices)
+    }
+}
 
-            // Then find every permutation containing the parent point, and add them to the polyhedron
-            // TODO: Some of those are definitely wrong. They are hidden, but should be taken care of before turning alpha channel on
-            for perm in adjacent_points.iter().permutations(2) {
-                polyhedron.push(current_point_3d);
-                for point in perm {
-                    polyhedron.push(vec_to_3d_point(point))
-                }
-            }
-        }
+fn points_nearly_equal(a: &[f32], b: &[f32]) -> bool {
+    a.iter().zip(b).all(|(a, b)| (a - b).abs() <= 0.0001)
+}
 
-        // We then center and scale the polyhedron
-        for value in center.iter_mut() {
-            *value /= seen.len() as f32
+/// Solves A.x = b using Gaussian elimination, None if A is singular
+fn solve_linear_system(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-9 {
+            return None;
         }
-        for vertex in polyhedron.iter_mut() {
-            for (value, offset) in vertex.iter_mut().zip(center.iter_mut()) {
-                *value -= *offset;
-                *value /= max_value;
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        for row in 0..n {
+            if row != col {
+                let factor = a[row][col] / a[col][col];
+                let pivot_row = a[col].clone();
+                for (value, pivot_value) in a[row].iter_mut().zip(pivot_row).skip(col) {
+                    *value -= factor * pivot_value;
+                }
+                b[row] -= factor * b[col];
             }
         }
-
-        polyhedron
     }
+    Some((0..n).map(|i| b[i] / a[i][i]).collect())
 }
 
 impl std::ops::Index<usize> for Constraints {
@@ -398,7 +669,7 @@ impl std::fmt::Display for Constraints {
 PARSING
  */
 impl std::str::FromStr for Operator {
-    type Err = ();
+    type Err = ParseError;
     /// Parses a string into an operator
     /// # Example
     /// ``` rust
@@ -413,19 +684,16 @@ impl std::str::FromStr for Operator {
     /// assert_eq!(operator, expected)
     /// ```
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.trim() {
-            "=" => Ok(Operator::Equal),
-            "<" => Ok(Operator::Less),
-            ">" => Ok(Operator::Greater),
-            "<=" => Ok(Operator::LessEqual),
-            ">=" => Ok(Operator::GreaterEqual),
-            _ => Err(()),
-        }
+        SPELLINGS
+            .iter()
+            .find(|(spelling, _)| *spelling == s.trim())
+            .map(|(_, operator)| *operator)
+            .ok_or_else(|| ParseError::unexpected(s, 0, &OPERATORS))
     }
 }
 
 impl std::str::FromStr for Constraint {
-    type Err = ();
+    type Err = ParseError;
 
     /// Parses a constraint from a string
     /// # Example
@@ -443,25 +711,17 @@ impl std::str::FromStr for Constraint {
     /// assert_eq!(constraint, expected)
     /// ```
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parse_op = alt((
-            tag::<&str, &str, ()>("<="),
-            tag::<&str, &str, ()>(">="),
-            tag::<&str, &str, ()>("="),
-            tag::<&str, &str, ()>("<"),
-            tag::<&str, &str, ()>(">"),
-        ));
-        if let Ok((rhs, (lhs, op))) = many_till(anychar, parse_op)(s) {
-            let lhs = lhs
-                .iter()
-                .fold(String::new(), |acc, c| acc + &c.to_string());
-            Ok(Constraint::new(
-                lhs.parse::<LinearFunction>()?,
-                op.parse()?,
-                rhs.parse::<LinearFunction>()?,
-            ))
-        } else {
-            Err(())
+        let mut constraints = Constraint::parse_chain(s)?;
+        if constraints.len() > 1 {
+            return Err(ParseError {
+                line: 0,
+                column: 0,
+                length: s.chars().count(),
+                expected: vec![],
+                message: "a chain of comparisons gives several constraints".to_string(),
+            });
         }
+        Ok(constraints.remove(0))
     }
 }
 
@@ -629,6 +889,32 @@ mod tests {
         assert_eq!(n, expected)
     }
 
+    #[test]
+    fn test_equality() {
+        let constraints = Constraints::compile("x + y = 2").unwrap();
+        assert_eq!(constraints[0].left, "ε0".parse::<LinearFunction>().unwrap());
+        assert_eq!(constraints[0].right, "2 - x - y".parse::<LinearFunction>().unwrap());
+        assert_eq!(constraints[1].left, "ε1".parse::<LinearFunction>().unwrap());
+        assert_eq!(constraints[1].right, "x + y - 2".parse::<LinearFunction>().unwrap());
+    }
+
+    #[test]
+    fn test_chain() {
+        let (constraints, lines) = Constraints::compile_with_lines("x ≥ 1\n1 <= x + y < 4").unwrap();
+        assert_eq!(constraints.gap_variables_count(), 3);
+        assert_eq!(lines, vec![0, 1, 1]);
+        assert_eq!(constraints[1].right, "x + y - 1".parse::<LinearFunction>().unwrap());
+        assert_eq!(constraints[2].right, "4 - x - y".parse::<LinearFunction>().unwrap());
+
+        for spelling in ["==", "=<", "≤", "≥"] {
+            assert!(spelling.parse::<Operator>().is_ok());
+        }
+        let error = "1 <= x + <= 4".parse::<Constraint>().unwrap_err();
+        assert_eq!(error.column, 7);
+        let error = "1 <= x <= 4".parse::<Constraint>().unwrap_err();
+        assert_eq!(error.message, "a chain of comparisons gives several constraints");
+    }
+
     #[test]
     fn test_normalize() {
         let mut constraints =
@@ -652,4 +938,54 @@ mod tests {
         c -= l_f;
         assert_eq!(c, expected);
     }
+
+    #[test]
+    fn test_polyhedron_cube() {
+        let constraints = Constraints::compile("x <= 1\n y <= 1\n z <= 1").unwrap();
+        let polyhedron = constraints.polyhedron();
+
+        assert_eq!(polyhedron.vertices.len(), 8);
+        assert_eq!(polyhedron.faces.len(), 12);
+    }
+
+    #[test]
+    fn test_ratios() {
+        let constraints = Constraints::compile("x <= 200\n x + y <= 300\n y <= 100").unwrap();
+
+        assert_eq!(
+            constraints.ratios(&"x".to_string()),
+            vec![Some(200.0), Some(300.0), None]
+        );
+    }
+
+    #[test]
+    fn test_fix() {
+        let constraints = Constraints::compile("x + y + z <= 4").unwrap();
+        let fixed = constraints.fix(&"z".to_string(), 1.0);
+
+        assert_eq!(fixed[0].right, "3 - x - y".parse::<LinearFunction>().unwrap());
+    }
+
+    #[test]
+    fn test_eliminate() {
+        let constraints = Constraints::compile("x + y <= 4\n z - y <= 1").unwrap();
+        let projected = constraints.eliminate(&"y".to_string());
+        let vertices = projected.vertices();
+
+        // x + z <= 5, along with x <= 4
+        assert_eq!(projected.gap_variables_count(), 2);
+        assert!(vertices.contains(&vec![0.0, 5.0]));
+        assert!(vertices.contains(&vec![4.0, 1.0]));
+        assert_eq!(vertices.len(), 4);
+    }
+
+    #[test]
+    fn test_enumerate_vertices() {
+        let constraints = Constraints::compile("x + y <= 4\n x + y >= 2").unwrap();
+        let vertices = constraints.vertices();
+
+        assert_eq!(vertices.len(), 4);
+        assert!(vertices.contains(&vec![2.0, 0.0]));
+        assert!(vertices.contains(&vec![0.0, 4.0]));
+    }
 }
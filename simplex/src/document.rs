@@ -0,0 +1,923 @@
+//! Text format of a whole linear program, so that a problem can be saved and shared as a
+//! single file
+//!
+//! ```text
+//! # Comments start with a hash
+//! param cap = [200, 300]
+//! max: x + 6y + 13z
+//! subject to
+//!   c1: x <= 200
+//!   x + y + z <= 400
+//! bounds
+//!   y <= 300
+//! int x
+//! free z
+//! end
+//! ```
+//! Constraints written before any section belong to `subject to`. Variables are nonnegative,
+//! unless declared free.
+//!
+//! Parameters are numbers or tables indexed from 1, used in the lines that follow them.
+//! A constraint or a bound ending with a `for` clause is written once for each value of its
+//! indices, and named after them: `cap: sum(j in 1..3) x[i][j] <= cap[i] for i in 1..2` gives
+//! `cap_1` and `cap_2`
+use crate::constraint::{Constraint, Constraints, Operator};
+use crate::error::ParseError;
+use crate::expression::{Context, Parser};
+use crate::format::{Format, Row};
+use crate::linear_function::{Coefficient, LinearFunction, Variable};
+use crate::rows::{flipped, split};
+use crate::LinearProgram;
+use itertools::Itertools;
+use std::collections::HashMap;
+
+pub use crate::expression::Table;
+pub use crate::rows::Bounds;
+
+/// Keywords of the objective, followed by a colon
+pub(crate) const OBJECTIVES: [(&str, Sense); 6] = [
+    ("max", Sense::Maximize),
+    ("maximize", Sense::Maximize),
+    ("maximise", Sense::Maximize),
+    ("min", Sense::Minimize),
+    ("minimize", Sense::Minimize),
+    ("minimise", Sense::Minimize),
+];
+pub(crate) const SUBJECT_TO: [&str; 4] = ["subject to", "such that", "s.t.", "st"];
+const INTEGERS: [&str; 2] = ["int", "integer"];
+
+/// Whether the objective is maximized or minimized
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Sense {
+    #[default]
+    Maximize,
+    Minimize,
+}
+
+/// Constraint of the `subject to` section, with its optional name
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedConstraint {
+    pub name: Option<String>,
+    pub constraint: Constraint,
+}
+
+/// A linear program as it is written, before being turned into a dictionary
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Document {
+    /// Parameters, in the order they are declared
+    pub parameters: Vec<(String, Table)>,
+    pub sense: Sense,
+    pub objective: LinearFunction,
+    pub constraints: Vec<NamedConstraint>,
+    /// Constraints on a single variable
+    pub bounds: Vec<Constraint>,
+    /// Variables declared integer, of which only the relaxation is solved
+    pub integers: Vec<Variable>,
+    /// Variables that can be negative
+    pub free: Vec<Variable>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Constraints,
+    Bounds,
+}
+
+/// Writes a number with subscript digits, like `₁₂`
+fn subscript(number: usize) -> String {
+    (number.to_string().chars())
+        .filter_map(|digit| char::from_u32('₀' as u32 + digit.to_digit(10)?))
+        .collect()
+}
+
+/// Reads a bound as `var operator value`, None if it doesn't apply to a single variable
+fn single_bound(bound: &Constraint) -> Option<(Variable, Operator, Coefficient)> {
+    let (function, operator, rhs) = split(bound);
+    let var = function.var_iter().next()?;
+    let operator = match function[var] < 0.0 {
+        true => flipped(operator),
+        false => operator,
+    };
+    Some((var.clone(), operator, rhs / function[var]))
+}
+
+/// Whether a word can name a variable, a parameter or a constraint: a letter followed by
+/// letters, digits and underscores
+pub(crate) fn is_name(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_alphabetic())
+        && word.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Whether a word is read as a keyword when it starts a line, and can't name anything
+pub(crate) fn is_keyword(word: &str) -> bool {
+    let word = word.to_lowercase();
+    OBJECTIVES.iter().any(|(keyword, _)| *keyword == word)
+        || INTEGERS.contains(&word.as_str())
+        || ["param", "free", "end", "bounds", "st"].contains(&word.as_str())
+}
+
+/// Error on a whole span of a line, given in bytes
+fn error_on(line: &str, start: usize, end: usize, message: String) -> ParseError {
+    ParseError {
+        line: 0,
+        column: line[..start].chars().count(),
+        length: line[start..end].chars().count(),
+        expected: vec![],
+        message,
+    }
+}
+
+/// What a line holds, told by its keywords
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Blank,
+    End,
+    Section(Section),
+    /// Objective, whose keyword ends with the colon at this byte offset
+    Objective(Sense, usize),
+    Parameter,
+    Integers,
+    Free,
+    /// Constraint or bound
+    Comparison,
+}
+
+/// Tells what a line holds, its comment being removed
+fn classify(code: &str) -> Kind {
+    let words = code.split_whitespace().join(" ").to_lowercase();
+    let first_word = words.split(' ').next().unwrap_or_default();
+    let objective = code.find(':').and_then(|colon| {
+        let keyword = code[..colon].trim().to_lowercase();
+        let found = OBJECTIVES.iter().find(|(k, _)| *k == keyword);
+        found.map(|&(_, sense)| Kind::Objective(sense, colon))
+    });
+
+    if words.is_empty() {
+        Kind::Blank
+    } else if words == "end" {
+        Kind::End
+    } else if SUBJECT_TO.contains(&words.as_str()) {
+        Kind::Section(Section::Constraints)
+    } else if words == "bounds" {
+        Kind::Section(Section::Bounds)
+    } else if let Some(objective) = objective {
+        objective
+    } else if first_word == "param" {
+        Kind::Parameter
+    } else if INTEGERS.contains(&first_word) {
+        Kind::Integers
+    } else if first_word == "free" {
+        Kind::Free
+    } else {
+        Kind::Comparison
+    }
+}
+
+/// Reads the variables declared after a keyword, separated by commas or spaces
+fn declared_variables(line: &str, start: usize) -> Result<Vec<Variable>, ParseError> {
+    let mut variables = vec![];
+    let mut offset = start;
+    for word in line[start..].split([',', ' ', '\t']) {
+        if !word.is_empty() {
+            if !is_name(word) {
+                return Err(ParseError::unexpected(line, offset, &["a variable"]));
+            }
+            variables.push(word.to_string());
+        }
+        offset += word.len() + 1;
+    }
+    if variables.is_empty() {
+        return Err(ParseError::unexpected(line, line.len(), &["a variable"]));
+    }
+    Ok(variables)
+}
+
+/// Rewrites the constraint of a line, keeping its indentation, name and comment
+/// ```rust
+/// use simplex::document::rewrite_constraint;
+/// let constraint = "x + y <= 3".parse().unwrap();
+/// assert_eq!(
+///     rewrite_constraint("  c1: x <= 2  # machine", &constraint),
+///     "  c1: x + y <= 3  # machine"
+/// );
+/// ```
+pub fn rewrite_constraint(line: &str, constraint: &Constraint) -> String {
+    let (code, comment) = line.split_at(line.find('#').unwrap_or(line.len()));
+    let start = match code.find(':') {
+        Some(colon) => colon + 1 + code[colon + 1..].len() - code[colon + 1..].trim_start().len(),
+        None => code.len() - code.trim_start().len(),
+    };
+    let spacing = &code[code.trim_end().len()..];
+    format!("{}{constraint}{spacing}{comment}", &code[..start])
+}
+
+/// Whether a line is written with sums, indices, parameters or a `for` clause, and is kept as
+/// it is when formatting
+fn is_templated(code: &str, parameters: &[(String, Table)]) -> bool {
+    code.contains('[')
+        || code
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .any(|word| word == "sum" || word == "for" || parameters.iter().any(|(p, _)| p == word))
+}
+
+/// Formats the text of a document in place, keeping its sections, names and comments.
+/// Consecutive constraints are aligned together, and lines written with sums, indices,
+/// parameters or a `for` clause are kept as they are
+/// ```rust
+/// use simplex::document::format_source;
+/// use simplex::format::Format;
+/// let text = "max:x+0.5y # profit\nsubject to\n  c1: x+y<=4\n  long: 2x   >= 1\n";
+/// let format = Format { align: true, ..Format::default() };
+/// assert_eq!(
+///     format_source(text, &format).unwrap(),
+///     "max: x + 0.5y  # profit\nsubject to\n  c1:   x + y <= 4\n  long: 2x    >= 1\n"
+/// );
+/// ```
+pub fn format_source(text: &str, format: &Format) -> Result<String, ParseError> {
+    let (document, _) = Document::parse(text)?;
+    let mut lines = vec![];
+    let mut rows = vec![];
+    let mut section = Section::Constraints;
+    let mut written = text.lines();
+
+    for line in written.by_ref() {
+        let (code, comment) = line.split_at(line.find('#').unwrap_or(line.len()));
+        let indent = &code[..code.len() - code.trim_start().len()];
+        let comment = comment.trim_end().to_string();
+        let kind = classify(code);
+
+        if kind == Kind::Comparison && !is_templated(code, &document.parameters) {
+            // Bounds have no names
+            let colon = code.find(':').filter(|_| section == Section::Constraints);
+            let (name, start) = match colon {
+                Some(colon) => (format!("{}:", code[..colon].trim()), colon + 1),
+                None => (String::new(), 0),
+            };
+            let chain = Constraint::parse_chain(&code[start..])?;
+            let sides: Vec<LinearFunction> = (chain.iter().take(1).map(|c| c.left.clone()))
+                .chain(chain.iter().map(|c| c.right.clone()))
+                .collect();
+            let operators: Vec<Operator> = chain.iter().map(|c| c.operator).collect();
+            let columns = format.columns(&sides, &operators);
+            let indent = indent.to_string();
+            rows.push(Row {
+                indent,
+                name,
+                columns,
+                comment,
+            });
+            continue;
+        }
+
+        lines.extend(format.rows(&rows));
+        rows.clear();
+        match kind {
+            Kind::Objective(_, colon) if !is_templated(code, &document.parameters) => {
+                let objective = Parser::parse(&code[colon + 1..])?;
+                let keyword = code[..colon + 1].trim_end();
+                let objective = format!("{keyword} {}", format.function(&objective));
+                lines.push(match comment.is_empty() {
+                    true => objective,
+                    false => format!("{objective}  {comment}"),
+                });
+            }
+            Kind::Section(next) => {
+                section = next;
+                lines.push(line.to_string());
+            }
+            _ => lines.push(line.to_string()),
+        }
+        if kind == Kind::End {
+            break;
+        }
+    }
+    lines.extend(format.rows(&rows));
+    // Lines after the end are kept as they are
+    lines.extend(written.map(String::from));
+
+    let mut formatted = lines.join("\n");
+    if text.ends_with('\n') {
+        formatted.push('\n');
+    }
+    Ok(formatted)
+}
+
+/// Returns the byte offset of the `for` clause of a line, if any
+fn for_clause(code: &str) -> Option<usize> {
+    code.match_indices("for").map(|(i, _)| i).find(|&i| {
+        let before = code[..i].chars().next_back();
+        let after = code[i + 3..].chars().next();
+        before.is_some_and(char::is_whitespace) && after.is_some_and(char::is_whitespace)
+    })
+}
+
+/// Parses the comparison of a line from a byte offset, once for each value of the indices of
+/// its `for` clause. The constraints come with the values of the indices they were written for
+fn expand(
+    code: &str,
+    start: usize,
+    context: &Context,
+) -> Result<Vec<(Vec<i64>, Constraint)>, ParseError> {
+    let (end, assignments) = match for_clause(code) {
+        Some(clause) => {
+            let assignments = Parser::parse_ranges(&code[clause + 3..], context.clone())
+                .map_err(|e| e.after(&code[..clause + 3]))?;
+            (clause, assignments)
+        }
+        None => (code.len(), vec![vec![]]),
+    };
+
+    let mut constraints = vec![];
+    for assignment in assignments {
+        let mut context = context.clone();
+        context.indices.extend(assignment.iter().cloned());
+        let chain = Constraint::parse_chain_with(&code[start..end], &context)
+            .map_err(|e| e.after(&code[..start]))?;
+        let values: Vec<i64> = assignment.into_iter().map(|(_, value)| value).collect();
+        constraints.extend(chain.into_iter().map(|c| (values.clone(), c)));
+    }
+    Ok(constraints)
+}
+
+impl Document {
+    /// Parses a document, along with the index of the line of each constraint then of each
+    /// bound
+    /// ```rust
+    /// use simplex::document::{Document, Sense};
+    /// let text = "min: x + y\nsubject to\n  c1: x + y >= 2\n\nbounds\n  x <= 3";
+    /// let (document, lines) = Document::parse(text).unwrap();
+    /// assert_eq!(document.sense, Sense::Minimize);
+    /// assert_eq!(document.constraints[0].name.as_deref(), Some("c1"));
+    /// assert_eq!(lines, vec![2, 5]);
+    /// ```
+    pub fn parse(text: &str) -> Result<(Document, Vec<usize>), ParseError> {
+        let mut document = Document::default();
+        let mut objective_line = None;
+        let mut section = Section::Constraints;
+        let mut context = Context::default();
+        let (mut constraint_lines, mut bound_lines) = (vec![], vec![]);
+        // Line of each constraint name, which can only be given to the comparisons of one line
+        let mut name_lines: HashMap<String, usize> = HashMap::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let code = line.split('#').next().unwrap_or_default();
+            let keyword_start = code.len() - code.trim_start().len();
+            let first_word = code.split_whitespace().next().unwrap_or_default();
+            let kind = classify(code);
+
+            if kind == Kind::Blank {
+                continue;
+            } else if kind == Kind::End {
+                break;
+            } else if let Kind::Section(next) = kind {
+                section = next;
+            } else if let Kind::Objective(sense, colon) = kind {
+                if let Some(first) = objective_line {
+                    return Err(error_on(
+                        code,
+                        keyword_start,
+                        colon,
+                        format!("the objective is already given on line {}", first + 1),
+                    )
+                    .on_line(i));
+                }
+                objective_line = Some(i);
+                document.sense = sense;
+                document.objective = Parser::parse_with(&code[colon + 1..], context.clone())
+                    .map_err(|e| e.after(&code[..colon + 1]).on_line(i))?;
+            } else if kind == Kind::Parameter {
+                let start = keyword_start + first_word.len();
+                let Some(equal) = code.find('=') else {
+                    return Err(ParseError::unexpected(code, code.len(), &["`=`"]).on_line(i));
+                };
+                let name = code[start..equal].trim();
+                if !is_name(name) {
+                    let spaces = code[start..].len() - code[start..].trim_start().len();
+                    let error = ParseError::unexpected(code, start + spaces, &["a name"]);
+                    return Err(error.on_line(i));
+                }
+                let table = Parser::parse_table(&code[equal + 1..], context.clone())
+                    .map_err(|e| e.after(&code[..equal + 1]).on_line(i))?;
+                context.parameters.insert(name.to_string(), table.clone());
+                document.parameters.push((name.to_string(), table));
+            } else if kind == Kind::Integers || kind == Kind::Free {
+                let variables = declared_variables(code, keyword_start + first_word.len())
+                    .map_err(|e| e.on_line(i))?;
+                if kind == Kind::Free {
+                    document.free.extend(variables);
+                } else {
+                    document.integers.extend(variables);
+                }
+            } else if section == Section::Bounds {
+                // Both sides of a range like `0 <= x <= 10` are bounds
+                for (_, bound) in expand(code, 0, &context).map_err(|e| e.on_line(i))? {
+                    let difference = bound.left.clone() - bound.right.clone();
+                    if difference.var_iter().count() != 1 {
+                        return Err(error_on(
+                            code,
+                            keyword_start,
+                            code.trim_end().len(),
+                            "a bound applies to a single variable".to_string(),
+                        )
+                        .on_line(i));
+                    }
+                    document.bounds.push(bound);
+                    bound_lines.push(i);
+                }
+            } else {
+                // A constraint can be named by a prefix ending with a colon
+                let (name, start) = match code.find(':') {
+                    Some(colon) => {
+                        let name = code[..colon].trim();
+                        if !is_name(name) {
+                            let error = ParseError::unexpected(code, keyword_start, &["a name"]);
+                            return Err(error.on_line(i));
+                        }
+                        (Some(name.to_string()), colon + 1)
+                    }
+                    None => (None, 0),
+                };
+                let name_end = keyword_start + code[..start].trim().len().saturating_sub(1);
+                for (values, constraint) in
+                    expand(code, start, &context).map_err(|e| e.on_line(i))?
+                {
+                    let name = match values.is_empty() {
+                        true => name.clone(),
+                        false => name.as_ref().map(|name| {
+                            std::iter::once(name.clone())
+                                .chain(values.iter().map(i64::to_string))
+                                .join("_")
+                        }),
+                    };
+                    if let Some(name) = &name {
+                        match name_lines.insert(name.clone(), i) {
+                            Some(line) if line != i => {
+                                let message = format!(
+                                    "`{name}` already names a constraint on line {}",
+                                    line + 1
+                                );
+                                let error = error_on(code, keyword_start, name_end, message);
+                                return Err(error.on_line(i));
+                            }
+                            _ => {}
+                        }
+                    }
+                    document
+                        .constraints
+                        .push(NamedConstraint { name, constraint });
+                    constraint_lines.push(i);
+                }
+            }
+        }
+
+        if objective_line.is_none() {
+            return Err(ParseError {
+                line: 0,
+                column: 0,
+                length: 0,
+                expected: vec![],
+                message: "missing objective, such as `max: x + y`".to_string(),
+            });
+        }
+        // Variables are nonnegative, so a negative lower bound needs the variable to be free
+        for (bound, &i) in document.bounds.iter().zip(&bound_lines) {
+            let Some((var, operator, value)) = single_bound(bound) else {
+                continue;
+            };
+            if operator != Operator::LessEqual && value < 0.0 && !document.free.contains(&var) {
+                let code = text.lines().nth(i).unwrap_or_default();
+                let code = code.split('#').next().unwrap_or_default().trim_end();
+                let start = code.len() - code.trim_start().len();
+                let message = format!("`{var}` can't be negative unless it is declared `free`");
+                return Err(error_on(code, start, code.len(), message).on_line(i));
+            }
+        }
+        constraint_lines.extend(bound_lines);
+        Ok((document, constraint_lines))
+    }
+
+    /// Writes the document, constraints and bounds being written in the given format
+    /// ```rust
+    /// use simplex::document::Document;
+    /// use simplex::format::Format;
+    /// let document: Document = "max: x + y\nc1: x + y <= 4\n2x <= 0.25".parse().unwrap();
+    /// let format = Format { align: true, fractions: true, ..Format::default() };
+    /// assert_eq!(
+    ///     document.format(&format),
+    ///     "max: x + y\nsubject to\n  c1: x + y <= 4\n      2x    <= 1/4\n"
+    /// );
+    /// ```
+    pub fn format(&self, format: &Format) -> String {
+        let row = |name: Option<&str>, sides: &[LinearFunction], operators: &[Operator]| Row {
+            indent: "  ".to_string(),
+            name: name.map(|name| format!("{name}:")).unwrap_or_default(),
+            columns: format.columns(sides, operators),
+            comment: String::new(),
+        };
+        let single = |constraint: &Constraint| {
+            let sides = [constraint.left.clone(), constraint.right.clone()];
+            row(None, &sides, &[constraint.operator])
+        };
+
+        let mut lines = vec![];
+        for (name, table) in &self.parameters {
+            lines.push(format!("param {name} = {table}"));
+        }
+        lines.push(format!(
+            "{}: {}",
+            self.sense,
+            format.function(&self.objective)
+        ));
+        if !self.constraints.is_empty() {
+            lines.push("subject to".to_string());
+            // Named constraints following each other on the same side, like the two sides of
+            // a range, are written back as a chain
+            let mut rows = vec![];
+            let mut constraints = self.constraints.iter().peekable();
+            while let Some(NamedConstraint { name, constraint }) = constraints.next() {
+                let mut sides = vec![constraint.left.clone(), constraint.right.clone()];
+                let mut operators = vec![constraint.operator];
+                while let Some(next) = constraints.next_if(|next| {
+                    name.is_some()
+                        && next.name == *name
+                        && sides.last() == Some(&next.constraint.left)
+                }) {
+                    sides.push(next.constraint.right.clone());
+                    operators.push(next.constraint.operator);
+                }
+                rows.push(row(name.as_deref(), &sides, &operators));
+            }
+            lines.extend(format.rows(&rows));
+        }
+        if !self.bounds.is_empty() {
+            lines.push("bounds".to_string());
+            let rows: Vec<Row> = self.bounds.iter().map(single).collect();
+            lines.extend(format.rows(&rows));
+        }
+        if !self.integers.is_empty() {
+            lines.push(format!("int {}", self.integers.join(", ")));
+        }
+        if !self.free.is_empty() {
+            lines.push(format!("free {}", self.free.join(", ")));
+        }
+        lines.into_iter().map(|line| line + "\n").collect()
+    }
+
+    /// Returns the bounds of the variables that are bounded or free, the tightest bounds
+    /// being kept
+    /// ```rust
+    /// use simplex::document::{Bounds, Document};
+    /// let document: Document = "max: x\nbounds\n  x <= 4\n  2x <= 6\n  -1 <= y\nfree y".parse().unwrap();
+    /// let bounds = document.variable_bounds();
+    /// assert_eq!(bounds["x"], Bounds { lower: Some(0.0), upper: Some(3.0) });
+    /// assert_eq!(bounds["y"], Bounds { lower: Some(-1.0), upper: None });
+    /// ```
+    pub fn variable_bounds(&self) -> HashMap<Variable, Bounds> {
+        let mut bounds: HashMap<Variable, Bounds> = (self.free.iter())
+            .map(|var| {
+                (
+                    var.clone(),
+                    Bounds {
+                        lower: None,
+                        upper: None,
+                    },
+                )
+            })
+            .collect();
+        for bound in &self.bounds {
+            let Some((var, operator, value)) = single_bound(bound) else {
+                continue;
+            };
+            let Bounds { lower, upper } = bounds.entry(var).or_default();
+            if operator != Operator::LessEqual {
+                *lower = Some(lower.map_or(value, |lower| lower.max(value)));
+            }
+            if operator != Operator::GreaterEqual {
+                *upper = Some(upper.map_or(value, |upper| upper.min(value)));
+            }
+        }
+        bounds
+    }
+
+    /// Bounds a variable, declaring it free if it can be negative
+    pub fn add_bounds(&mut self, var: &Variable, bounds: Bounds) {
+        let single = || LinearFunction::single_variable(var.clone());
+        let constant = |value| LinearFunction::new(value, HashMap::new());
+        if bounds.lower.map_or(true, |lower| lower < 0.0) && !self.free.contains(var) {
+            self.free.push(var.clone());
+        }
+        let mut bound = |operator, value| {
+            let bound = Constraint::new(single(), operator, constant(value));
+            self.bounds.push(bound);
+        };
+        match (bounds.lower, bounds.upper) {
+            (Some(lower), Some(upper)) if lower == upper => return bound(Operator::Equal, lower),
+            (Some(lower), _) if lower != 0.0 => bound(Operator::GreaterEqual, lower),
+            _ => {}
+        }
+        if let Some(upper) = bounds.upper {
+            bound(Operator::LessEqual, upper);
+        }
+    }
+
+    /// Returns the program to solve, along with the index of the constraint or bound each
+    /// of its rows comes from. Constraints come first, then bounds, and give their name to
+    /// their gap variables.
+    /// A minimized objective is negated, and free variables are written as the difference of
+    /// two nonnegative ones
+    pub fn program(&self) -> (LinearProgram, Vec<usize>) {
+        let split = |function: &LinearFunction| {
+            let mut function = function.clone();
+            for var in &self.free {
+                let positive = LinearFunction::single_variable(format!("{var}⁺"));
+                let negative = LinearFunction::single_variable(format!("{var}⁻"));
+                function.replace(var, &(positive - negative));
+            }
+            function
+        };
+
+        // Constraints sharing a name, like the sides of a chain, are told apart by a subscript
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for name in self.constraints.iter().filter_map(|c| c.name.as_deref()) {
+            *counts.entry(name).or_default() += 1;
+        }
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        let names: Vec<Option<String>> = (self.constraints.iter())
+            .map(|c| {
+                let name = c.name.as_deref()?;
+                if counts[name] == 1 {
+                    return Some(name.to_string());
+                }
+                let occurrence = seen.entry(name).or_default();
+                *occurrence += 1;
+                Some(format!("{name}{}", subscript(*occurrence)))
+            })
+            .collect();
+
+        let mut constraints = Constraints::new();
+        let mut items = vec![];
+        let written =
+            (self.constraints.iter().zip(&names)).map(|(c, name)| (name.as_deref(), &c.constraint));
+        let bounds = self.bounds.iter().map(|bound| (None, bound));
+        for (item, (name, constraint)) in written.chain(bounds).enumerate() {
+            constraints.add_named_constraint(
+                Constraint::new(
+                    split(&constraint.left),
+                    constraint.operator,
+                    split(&constraint.right),
+                ),
+                name,
+            );
+            items.resize(constraints.gap_variables_count(), item);
+        }
+        let objective = split(&self.objective);
+        let linear_function = match self.sense {
+            Sense::Maximize => objective,
+            Sense::Minimize => -objective,
+        };
+        (
+            LinearProgram {
+                linear_function,
+                constraints,
+            },
+            items,
+        )
+    }
+}
+
+impl From<&LinearProgram> for Document {
+    /// Writes a program as a document, each row `εi = c + a·x` becoming `c + a·x >= 0`
+    fn from(program: &LinearProgram) -> Document {
+        let constraints = program
+            .constraints
+            .iter()
+            .map(|row| NamedConstraint {
+                name: None,
+                constraint: Constraint::new(
+                    row.right.clone(),
+                    Operator::GreaterEqual,
+                    LinearFunction::zero(),
+                ),
+            })
+            .collect();
+        Document {
+            objective: program.linear_function.clone(),
+            constraints,
+            ..Document::default()
+        }
+    }
+}
+
+impl std::str::FromStr for Document {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Document::parse(s).map(|(document, _)| document)
+    }
+}
+
+impl std::fmt::Display for Sense {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sense::Maximize => write!(f, "max"),
+            Sense::Minimize => write!(f, "min"),
+        }
+    }
+}
+
+impl std::fmt::Display for Document {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.format(&Format::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+# Three products
+maximize: x + 6y + 13z
+subject to
+  c1: x <= 200   # first machine
+  y + 3z = 600
+bounds
+  0 ≤ y ≤ 300
+int x, y
+free z
+end
+this line is ignored";
+
+    #[test]
+    fn test_sections() {
+        let (document, lines) = Document::parse(EXAMPLE).unwrap();
+        assert_eq!(document.sense, Sense::Maximize);
+        assert_eq!(document.constraints.len(), 2);
+        assert_eq!(document.constraints[0].name.as_deref(), Some("c1"));
+        assert_eq!(document.constraints[1].name, None);
+        assert_eq!(document.constraints[1].constraint.operator, Operator::Equal);
+        assert_eq!(document.bounds.len(), 2);
+        assert_eq!(document.integers, vec!["x", "y"]);
+        assert_eq!(document.free, vec!["z"]);
+        assert_eq!(lines, vec![3, 4, 6, 6]);
+
+        // The equality gives two rows, and z is split in two
+        let (program, items) = document.program();
+        assert_eq!(items, vec![0, 1, 1, 2, 3]);
+        assert_eq!(program.constraints[1].right[&"z⁺".to_string()], -3.0);
+        assert_eq!(program.constraints[1].right[&"z⁻".to_string()], 3.0);
+        assert!(!program.linear_function.contains(&"z".to_string()));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let document = EXAMPLE.parse::<Document>().unwrap();
+        let printed = document.to_string();
+        assert_eq!(printed.parse::<Document>().unwrap(), document);
+
+        // Constraints written before any section, with a minimized objective
+        let document = "x + y >= 2\nmin: 2x + y".parse::<Document>().unwrap();
+        assert_eq!(document.sense, Sense::Minimize);
+        assert_eq!(
+            document.to_string(),
+            "min: 2x + y\nsubject to\n  x + y >= 2\n"
+        );
+        assert_eq!(document.program().0.linear_function[&"x".to_string()], -2.0);
+
+        // A named chain is written back as such, each of its rows getting its own slack
+        let document = "max: x + y\ncap: 0 <= x + y <= 10"
+            .parse::<Document>()
+            .unwrap();
+        assert_eq!(
+            document.to_string(),
+            "max: x + y\nsubject to\n  cap: 0 <= x + y <= 10\n"
+        );
+        let (program, _) = document.program();
+        assert_eq!(
+            program.constraints.display_name(&"ε0".to_string()),
+            "slack(cap₁)"
+        );
+        assert_eq!(
+            program.constraints.display_name(&"ε1".to_string()),
+            "slack(cap₂)"
+        );
+    }
+
+    #[test]
+    fn test_parametric() {
+        let text = "\
+param cap = [10, 20]
+param cost = [[1, 2, 3], [4, 5, 6]]
+min: sum(i in 1..2, j in 1..3) cost[i][j] x[i][j]
+subject to
+  cap: sum(j in 1..3) x[i][j] <= cap[i] for i in 1..2
+  sum(i in 1..2) x[i][j] >= j for j in 1..3";
+        let (document, lines) = Document::parse(text).unwrap();
+        assert_eq!(document.constraints.len(), 5);
+        assert_eq!(lines, vec![4, 4, 5, 5, 5]);
+        assert_eq!(document.constraints[1].name.as_deref(), Some("cap_2"));
+        assert_eq!(
+            document.constraints[1].constraint.to_string(),
+            "x_2_1 + x_2_2 + x_2_3 <= 20"
+        );
+        assert_eq!(
+            document.constraints[4].constraint.to_string(),
+            "x_1_3 + x_2_3 >= 3"
+        );
+        assert_eq!(document.objective[&"x_2_3".to_string()], 6.0);
+
+        // Parameters are printed, and the constraints expanded
+        let printed = document.to_string();
+        assert!(printed.starts_with("param cap = [10, 20]\nparam cost = [[1, 2, 3], [4, 5, 6]]\n"));
+        assert_eq!(printed.parse::<Document>().unwrap(), document);
+
+        let e = Document::parse("param cap = [10, 20]\nmax: x\nx <= cap[3]").unwrap_err();
+        assert_eq!((e.line, e.column, e.length), (2, 5, 6));
+        assert_eq!(e.message, "`cap[3]` doesn't match the dimensions of `cap`");
+
+        let e = Document::parse("max: x\nx[i] <= 1 for i in 1..1000000000").unwrap_err();
+        assert_eq!((e.line, e.column, e.length), (1, 14, 18));
+        assert_eq!(e.message, "ranges can't take more than 100000 values");
+    }
+
+    #[test]
+    fn test_format() {
+        let formats = [
+            Format::default(),
+            Format {
+                fractions: true,
+                align: true,
+                ..Format::default()
+            },
+        ];
+        // Every number is written so that it is read back the same
+        let document: Document = "max: 0.1x + y / 3\nc1: 0.25x + 1e-7y <= 2/7\nbounds\n  y <= 1e9"
+            .parse()
+            .unwrap();
+        for format in &formats {
+            let printed = document.format(format);
+            assert_eq!(printed.parse::<Document>().unwrap(), document, "{printed}");
+        }
+
+        // Formatting twice changes nothing, and keeps the comments and the lines after the end
+        for format in &formats {
+            let formatted = format_source(EXAMPLE, format).unwrap();
+            assert_eq!(format_source(&formatted, format).unwrap(), formatted);
+            assert_eq!(
+                formatted.parse::<Document>().unwrap(),
+                EXAMPLE.parse().unwrap()
+            );
+            assert!(formatted.contains("# first machine"));
+            assert!(formatted.ends_with("end\nthis line is ignored"));
+        }
+        let formatted = format_source(EXAMPLE, &formats[1]).unwrap();
+        assert!(formatted.contains("\n  c1: x      <= 200  # first machine\n      y + 3z  = 600\n"));
+        assert!(formatted.contains("\n  0 <= y <= 300\n"));
+
+        // Sums and parameters are kept as they are written
+        let text = "param cap = [10, 20]\nmax: sum(i in 1..2) x[i]\nx_1 <= cap[1]\n  x_2<=cap[2]";
+        let formatted = format_source(text, &formats[0]).unwrap();
+        assert_eq!(formatted, text);
+
+        let e = format_source("max: x\nx <= ", &formats[0]).unwrap_err();
+        assert_eq!(e.line, 1);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |text: &str| Document::parse(text).unwrap_err();
+
+        let e = error("max: x\nsubject to\n  c1: x + <= 2");
+        assert_eq!((e.line, e.column), (2, 8));
+
+        let e = error("max: x\nmin: y");
+        assert_eq!((e.line, e.column, e.length), (1, 0, 3));
+        assert_eq!(e.message, "the objective is already given on line 1");
+
+        let e = error("max: x\nbounds\n  x + y <= 3");
+        assert_eq!((e.line, e.column, e.length), (2, 2, 10));
+
+        let e = error("max: x\nbounds\n  x >= -5  # below zero");
+        assert_eq!((e.line, e.column, e.length), (2, 2, 7));
+        assert_eq!(
+            e.message,
+            "`x` can't be negative unless it is declared `free`"
+        );
+        assert!(Document::parse("max: x\nbounds\n  -5 <= x <= 5\nfree x").is_ok());
+
+        let e = error("max: x\ncap: x <= 1\n  cap: x <= 2");
+        assert_eq!((e.line, e.column, e.length), (2, 2, 3));
+        assert_eq!(e.message, "`cap` already names a constraint on line 2");
+
+        let e = error("max: x\nint x, 2y");
+        assert_eq!((e.line, e.column, e.length), (1, 7, 2));
+
+        let e = error("max: x\nc 1: x <= 2");
+        assert_eq!(e.expected, vec!["a name"]);
+
+        assert_eq!(
+            error("x <= 2").message,
+            "missing objective, such as `max: x + y`"
+        );
+    }
+}
@@ -1,5 +1,90 @@
 #[derive(Debug)]
 pub enum SimplexError {
     Unbounded,
-    AlreadyOptimal
-}
\ No newline at end of file
+    AlreadyOptimal,
+    InvalidPivot,
+}
+
+/// Error met while parsing the text of a program, located in that text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line of the error, starting at 0
+    pub line: usize,
+    /// Column of the first character of the offending span, in characters, starting at 0
+    pub column: usize,
+    /// Length of the offending span, in characters
+    pub length: usize,
+    /// What would have been accepted instead
+    pub expected: Vec<String>,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Builds an error on the token starting at a byte offset of a line,
+    /// or on the last character written when the line ends there
+    pub fn unexpected(text: &str, offset: usize, expected: &[&str]) -> ParseError {
+        let rest = &text[offset..];
+        let token_length = match rest.chars().next() {
+            Some(c) if c.is_alphanumeric() || c == '.' => rest
+                .find(|c: char| !c.is_alphanumeric() && c != '.')
+                .unwrap_or(rest.len()),
+            Some(c) => c.len_utf8(),
+            None => 0,
+        };
+        let (column, length, message) = if token_length == 0 {
+            // The last character written is underlined
+            let length = text.trim_end().chars().count();
+            (
+                length.saturating_sub(1),
+                length.min(1),
+                "unexpected end of expression".to_string(),
+            )
+        } else {
+            let token = &rest[..token_length];
+            (
+                text[..offset].chars().count(),
+                token.chars().count(),
+                format!("unexpected `{token}`"),
+            )
+        };
+        ParseError {
+            line: 0,
+            column,
+            length,
+            expected: expected.iter().map(|e| e.to_string()).collect(),
+            message,
+        }
+    }
+
+    /// Moves the error to a line of a longer text
+    pub fn on_line(self, line: usize) -> ParseError {
+        ParseError { line, ..self }
+    }
+
+    /// Moves the error to the right, when the text parsed started after a prefix
+    pub fn after(self, prefix: &str) -> ParseError {
+        ParseError {
+            column: self.column + prefix.chars().count(),
+            ..self
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line + 1,
+            self.column + 1,
+            self.message
+        )?;
+        match &self.expected[..] {
+            [] => Ok(()),
+            [expected] => write!(f, ", expected {expected}"),
+            [first @ .., last] => write!(f, ", expected {} or {last}", first.join(", ")),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
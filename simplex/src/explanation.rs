@@ -0,0 +1,237 @@
+//! Explanation of a step of the simplex algorithm, for students
+use crate::linear_function::{Coefficient, Variable};
+use crate::{LinearProgram, PivotRule};
+use std::fmt::Write;
+
+/// Why a variable was chosen to enter the basis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnteringReason {
+    /// Bland's rule: the first variable with a positive coefficient, in alphabetical order
+    Bland,
+    /// The first variable with a positive coefficient found, in no particular order
+    FirstPositive,
+    /// Dantzig's rule: the variable with the largest coefficient
+    Dantzig,
+    /// Steepest edge rule: the variable improving the objective the most per unit of length
+    SteepestEdge,
+    /// The variable was chosen by hand
+    Chosen,
+}
+
+impl From<PivotRule> for EnteringReason {
+    fn from(rule: PivotRule) -> Self {
+        match rule {
+            PivotRule::Bland => EnteringReason::Bland,
+            PivotRule::Dantzig => EnteringReason::Dantzig,
+            PivotRule::SteepestEdge => EnteringReason::SteepestEdge,
+        }
+    }
+}
+
+/// A row of the ratio test, on the constraint basic = constant + coefficient * entering + ...
+#[derive(Debug, Clone, PartialEq)]
+pub struct RatioRow {
+    pub basic: Variable,
+    pub constant: Coefficient,
+    pub coefficient: Coefficient,
+    /// How much the entering variable can increase before the basic one reaches 0,
+    /// None if the row doesn't limit it
+    pub ratio: Option<Coefficient>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    French,
+}
+
+/// Everything that happened during a step of the algorithm
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepExplanation {
+    pub entering: Variable,
+    /// Coefficient of the entering variable in the objective
+    pub coefficient: Coefficient,
+    pub reason: EnteringReason,
+    pub ratios: Vec<RatioRow>,
+    pub leaving: Variable,
+    pub previous_value: Coefficient,
+    pub objective_value: Coefficient,
+}
+
+impl StepExplanation {
+    /// Explains the pivot leading from a state to the next one, None if they aren't
+    /// separated by a pivot. Gap variables of named rows are called `slack(name)`
+    pub fn new(
+        previous: &LinearProgram,
+        next: &LinearProgram,
+        reason: EnteringReason,
+    ) -> Option<StepExplanation> {
+        let pivot = previous.pivot_to(next)?;
+        let name = |var: &Variable| previous.constraints.display_name(var);
+        let ratios = previous
+            .constraints
+            .iter()
+            .zip(previous.constraints.ratios(&pivot.entering))
+            .map(|(constraint, ratio)| RatioRow {
+                basic: name(&constraint.left.to_string()),
+                constant: constraint.right.constant,
+                coefficient: constraint.right[&pivot.entering],
+                ratio,
+            })
+            .collect();
+
+        Some(StepExplanation {
+            coefficient: previous.linear_function[&pivot.entering],
+            entering: name(&pivot.entering),
+            reason,
+            ratios,
+            leaving: name(&pivot.leaving),
+            previous_value: previous.objective_value(),
+            objective_value: next.objective_value(),
+        })
+    }
+
+    /// Writes the explanation as a few sentences
+    pub fn to_prose(&self, language: Language) -> String {
+        let StepExplanation {
+            entering,
+            coefficient,
+            leaving,
+            ..
+        } = self;
+        let mut prose = String::new();
+
+        let improves = *coefficient > 0.0;
+        match (language, self.reason) {
+            (Language::English, EnteringReason::Chosen) => write!(
+                prose,
+                "{entering} was chosen to enter the basis: its coefficient in the objective is {coefficient:.2}"
+            ),
+            (Language::English, _) => write!(
+                prose,
+                "{entering} enters the basis: its coefficient in the objective is {coefficient:.2}"
+            ),
+            (Language::French, EnteringReason::Chosen) => write!(
+                prose,
+                "{entering} a été choisie pour entrer dans la base : son coefficient dans l'objectif vaut {coefficient:.2}"
+            ),
+            (Language::French, _) => write!(
+                prose,
+                "{entering} entre dans la base : son coefficient dans l'objectif vaut {coefficient:.2}"
+            ),
+        }
+        .unwrap();
+        prose += match (language, improves) {
+            (Language::English, true) => {
+                ", which is positive, so increasing it improves the objective."
+            }
+            (Language::English, false) => {
+                ", which isn't positive, so increasing it can't improve the objective."
+            }
+            (Language::French, true) => ", qui est positif, donc l'augmenter améliore l'objectif.",
+            (Language::French, false) => {
+                ", qui n'est pas positif, donc l'augmenter ne peut pas améliorer l'objectif."
+            }
+        };
+        prose += match (language, self.reason) {
+            (Language::English, EnteringReason::Bland) => {
+                " Bland's rule picks the first such variable in alphabetical order.\n"
+            }
+            (Language::English, EnteringReason::FirstPositive) => {
+                " The first such variable found is picked.\n"
+            }
+            (Language::French, EnteringReason::Bland) => {
+                " La règle de Bland choisit la première variable de ce type dans l'ordre alphabétique.\n"
+            }
+            (Language::French, EnteringReason::FirstPositive) => {
+                " La première variable de ce type trouvée est choisie.\n"
+            }
+            (Language::English, EnteringReason::Dantzig) => {
+                " Dantzig's rule picks the variable with the largest coefficient.\n"
+            }
+            (Language::French, EnteringReason::Dantzig) => {
+                " La règle de Dantzig choisit la variable de plus grand coefficient.\n"
+            }
+            (Language::English, EnteringReason::SteepestEdge) => {
+                " The steepest edge rule picks the variable whose edge improves the objective the most per unit of length.\n"
+            }
+            (Language::French, EnteringReason::SteepestEdge) => {
+                " La règle de la plus forte pente choisit la variable dont l'arête améliore le plus l'objectif par unité de longueur.\n"
+            }
+            (_, EnteringReason::Chosen) => "\n",
+        };
+
+        let rows = self
+            .ratios
+            .iter()
+            .map(|row| match (row.ratio, language) {
+                (Some(ratio), _) => format!(
+                    "{}: {:.2} / {:.2} = {ratio:.2}",
+                    row.basic, row.constant, -row.coefficient
+                ),
+                (None, Language::English) => format!("{}: no limit", row.basic),
+                (None, Language::French) => format!("{} : pas de limite", row.basic),
+            })
+            .collect::<Vec<_>>();
+        match language {
+            Language::English => writeln!(prose, "Ratio test on {entering}: {}.", rows.join("; ")),
+            Language::French => writeln!(
+                prose,
+                "Test du ratio sur {entering} : {}.",
+                rows.join(" ; ")
+            ),
+        }
+        .unwrap();
+
+        let (previous_value, objective_value) = (self.previous_value, self.objective_value);
+        match language {
+            Language::English => write!(
+                prose,
+                "{leaving} has the smallest ratio, so it leaves the basis.\n\
+                The objective goes from {previous_value:.2} to {objective_value:.2}."
+            ),
+            Language::French => write!(
+                prose,
+                "{leaving} a le plus petit ratio, elle sort donc de la base.\n\
+                L'objectif passe de {previous_value:.2} à {objective_value:.2}."
+            ),
+        }
+        .unwrap();
+        prose
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::Constraints;
+    use crate::Simplex;
+
+    #[test]
+    fn test_step_explanation() {
+        let lp = LinearProgram {
+            linear_function: "x + 2y".parse().unwrap(),
+            constraints: Constraints::compile("x + y <= 4\n x <= 3").unwrap(),
+        };
+        let mut simplex = Simplex::from(lp);
+        let explanation = simplex.next_step(true).unwrap();
+
+        assert_eq!(explanation.entering, "x");
+        assert_eq!(explanation.coefficient, 1.0);
+        assert_eq!(explanation.reason, EnteringReason::Bland);
+        assert_eq!(explanation.leaving, "ε1");
+        assert_eq!(explanation.ratios[0].ratio, Some(4.0));
+        assert_eq!(explanation.ratios[1].ratio, Some(3.0));
+        assert_eq!(explanation.objective_value, 3.0);
+        assert_eq!(simplex.explanation(1), Some(explanation.clone()));
+
+        let english = explanation.to_prose(Language::English);
+        assert!(
+            english.contains("Ratio test on x: ε0: 4.00 / 1.00 = 4.00; ε1: 3.00 / 1.00 = 3.00.")
+        );
+        assert!(english.ends_with("The objective goes from 0.00 to 3.00."));
+        let french = explanation.to_prose(Language::French);
+        assert!(french.contains("ε1 a le plus petit ratio, elle sort donc de la base."));
+    }
+}
@@ -0,0 +1,567 @@
+//! Parser of linear expressions, written the way they are on exercise sheets:
+//! `2(x + y) - x/3 + 1.5e2`
+//!
+//! ```text
+//! expression := term (('+' | '-') term)*
+//! term       := factor (('*' | '/')? factor)*
+//! factor     := ('+' | '-') factor | number | '(' expression ')'
+//!             | 'sum' '(' ranges ')' term | name ('[' expression ']')*
+//! ranges     := name 'in' expression '..' expression (',' ranges)?
+//! ```
+//! A factor right after another one multiplies it, if it starts with a letter or a parenthesis.
+//! Products and quotients are folded as they are read, and must stay linear.
+//!
+//! Names are read as numbers when they are parameters or indices of the context, and
+//! indexed variables get their indices appended: `x[1][2]` is `x_1_2`.
+//! What an empty range applies to is read without being evaluated, and ranges expand to
+//! `MAX_EXPANSION` values at most
+use crate::error::ParseError;
+use crate::linear_function::{Coefficient, LinearFunction};
+use itertools::Itertools;
+use std::collections::HashMap;
+
+const FACTOR: [&str; 3] = ["a number", "a variable", "`(`"];
+
+/// Largest number of values the ranges of an expression take altogether
+const MAX_EXPANSION: usize = 100_000;
+
+/// Value of a parameter, a number or a table of them indexed from 1
+#[derive(Debug, Clone, PartialEq)]
+pub enum Table {
+    Number(Coefficient),
+    Rows(Vec<Table>),
+}
+
+/// Names read as numbers: parameters, and indices of sums and `for` clauses
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    pub parameters: HashMap<String, Table>,
+    pub indices: HashMap<String, i64>,
+}
+
+/// Values given to indices, in the order of their ranges
+pub type Assignment = Vec<(String, i64)>;
+
+impl std::fmt::Display for Table {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Table::Number(number) => write!(f, "{number}"),
+            Table::Rows(rows) => write!(f, "[{}]", rows.iter().join(", ")),
+        }
+    }
+}
+
+/// Reads a linear expression, keeping track of the position in the text for errors
+pub struct Parser<'a> {
+    text: &'a str,
+    /// Byte offset of the next character to read
+    position: usize,
+    context: Context,
+    /// Reading without evaluating, to go past what an empty range applies to
+    skipping: bool,
+    /// Number of values taken by the ranges read so far
+    expanded: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(text: &'a str, context: Context) -> Parser<'a> {
+        Parser {
+            text,
+            position: 0,
+            context,
+            skipping: false,
+            expanded: 0,
+        }
+    }
+
+    /// Returns the next character that isn't a whitespace, without reading it
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.text[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+        self.text[self.position..].chars().next()
+    }
+
+    /// Reads the next character if it is the one given
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Error on the token at the current position
+    fn unexpected(&mut self, expected: &[&str]) -> ParseError {
+        self.peek();
+        ParseError::unexpected(self.text, self.position, expected)
+    }
+
+    /// Error on the text read since a position
+    fn error_since(&self, start: usize, message: String) -> ParseError {
+        ParseError {
+            line: 0,
+            column: self.text[..start].chars().count(),
+            length: self.text[start..self.position].chars().count(),
+            expected: vec![],
+            message,
+        }
+    }
+
+    /// Reads a whole text as a single expression
+    pub fn parse(text: &str) -> Result<LinearFunction, ParseError> {
+        Parser::parse_with(text, Context::default())
+    }
+
+    /// Reads a whole text as a single expression, with parameters and indices
+    pub fn parse_with(text: &str, context: Context) -> Result<LinearFunction, ParseError> {
+        let mut parser = Parser::new(text, context);
+        let expression = parser.expression()?;
+        parser.end(&["`+`", "`-`"])?;
+        Ok(expression)
+    }
+
+    /// Reads a whole text as the ranges of a `for` clause, returning every value of its indices
+    pub fn parse_ranges(text: &str, context: Context) -> Result<Vec<Assignment>, ParseError> {
+        let mut parser = Parser::new(text, context);
+        let assignments = parser.assignments()?;
+        parser.end(&["`,`"])?;
+        Ok(assignments)
+    }
+
+    /// Reads a whole text as a number or a table of them, like `[[1, 2], [3, 4]]`
+    pub fn parse_table(text: &str, context: Context) -> Result<Table, ParseError> {
+        let mut parser = Parser::new(text, context);
+        let table = parser.table()?;
+        parser.end(&[])?;
+        Ok(table)
+    }
+
+    /// Checks that the whole text was read
+    fn end(&mut self, expected: &[&str]) -> Result<(), ParseError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.unexpected(expected)),
+        }
+    }
+
+    pub fn expression(&mut self) -> Result<LinearFunction, ParseError> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+') {
+                value += self.term()?;
+            } else if self.eat('-') {
+                value -= self.term()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<LinearFunction, ParseError> {
+        self.peek();
+        let start = self.position;
+        let mut value = self.factor()?;
+        loop {
+            if self.eat('*') {
+                let factor = self.factor()?;
+                value = self.multiply(value, factor, start)?;
+            } else if self.eat('/') {
+                let divisor = self.factor()?;
+                value = self.divide(value, divisor, start)?;
+            } else if matches!(self.peek(), Some(c) if c.is_alphabetic() || c == '(') {
+                let factor = self.factor()?;
+                value = self.multiply(value, factor, start)?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn factor(&mut self) -> Result<LinearFunction, ParseError> {
+        if self.eat('-') {
+            return Ok(-self.factor()?);
+        }
+        if self.eat('+') {
+            return self.factor();
+        }
+        if self.eat('(') {
+            let value = self.expression()?;
+            if !self.eat(')') {
+                return Err(self.unexpected(&["`)`"]));
+            }
+            return Ok(value);
+        }
+        if let Some(number) = self.number() {
+            return Ok(LinearFunction::new(number, Default::default()));
+        }
+        let start = self.position;
+        if let Some(name) = self.variable() {
+            if name == "sum" && self.peek() == Some('(') {
+                return self.sum();
+            }
+            return self.name(name, start);
+        }
+        Err(self.unexpected(&FACTOR))
+    }
+
+    /// Reads the indices following a name, and gives the value of the parameter, of the index or
+    /// of the variable it names
+    fn name(&mut self, name: &str, start: usize) -> Result<LinearFunction, ParseError> {
+        let mut indices = vec![];
+        while self.eat('[') {
+            indices.push(self.index()?);
+            if !self.eat(']') {
+                return Err(self.unexpected(&["`]`"]));
+            }
+        }
+        if self.skipping {
+            return Ok(LinearFunction::new(1.0, Default::default()));
+        }
+
+        if let Some(table) = self.context.parameters.get(name) {
+            let mut value = Some(table);
+            for &index in &indices {
+                value = match value {
+                    Some(Table::Rows(rows)) if index >= 1 => rows.get(index as usize - 1),
+                    _ => None,
+                };
+            }
+            let written = self.text[start..self.position].trim();
+            return match value {
+                Some(Table::Number(number)) => Ok(LinearFunction::new(*number, Default::default())),
+                _ => Err(self.error_since(
+                    start,
+                    format!("`{written}` doesn't match the dimensions of `{name}`"),
+                )),
+            };
+        }
+        match self.context.indices.get(name) {
+            Some(&index) if indices.is_empty() => Ok(LinearFunction::new(
+                index as Coefficient,
+                Default::default(),
+            )),
+            _ if indices.iter().any(|&index| index < 0) => {
+                Err(self.error_since(start, "indices of variables can't be negative".to_string()))
+            }
+            _ => Ok(LinearFunction::single_variable(
+                std::iter::once(name.to_string())
+                    .chain(indices.iter().map(i64::to_string))
+                    .join("_"),
+            )),
+        }
+    }
+
+    /// Reads an expression that has to be an integer, such as an index or the bound of a range
+    fn index(&mut self) -> Result<i64, ParseError> {
+        self.peek();
+        let start = self.position;
+        let value = self.expression()?;
+        if self.skipping {
+            return Ok(0);
+        }
+        if !value.is_constant() || value.constant.fract() != 0.0 {
+            let written = self.text[start..self.position].trim();
+            return Err(self.error_since(start, format!("`{written}` isn't an integer")));
+        }
+        Ok(value.constant as i64)
+    }
+
+    /// Reads the range of an index, like `i in 1..n`
+    fn range(&mut self) -> Result<(String, i64, i64), ParseError> {
+        let Some(index) = self.variable() else {
+            return Err(self.unexpected(&["an index"]));
+        };
+        let before = self.position;
+        if self.variable() != Some("in") {
+            self.position = before;
+            return Err(self.unexpected(&["`in`"]));
+        }
+        let low = self.index()?;
+        if !(self.eat('.') && self.eat('.')) {
+            return Err(self.unexpected(&["`..`"]));
+        }
+        let high = self.index()?;
+        Ok((index.to_string(), low, high))
+    }
+
+    /// Reads ranges separated by commas, then what follows them with `body`, for every value
+    /// of their indices. Each range is read again for every value of the indices before it, on
+    /// which it can depend: `i in 1..n, j in i..n`
+    fn over_ranges<T>(
+        &mut self,
+        body: &mut dyn FnMut(&mut Parser<'a>) -> Result<T, ParseError>,
+    ) -> Result<Vec<(Assignment, T)>, ParseError> {
+        self.peek();
+        let start = self.position;
+        let (index, low, high) = self.range()?;
+        let rest = self.position;
+        let previous = self.context.indices.get(&index).copied();
+        let skipping = self.skipping;
+        let mut results = vec![];
+        // An empty range is still read once without being evaluated, to go past what follows
+        for value in low..=high.max(low) {
+            self.expanded += 1;
+            if self.expanded > MAX_EXPANSION {
+                self.position = rest;
+                let message = format!("ranges can't take more than {MAX_EXPANSION} values");
+                return Err(self.error_since(start, message));
+            }
+            self.skipping = skipping || value > high;
+            self.context.indices.insert(index.clone(), value);
+            self.position = rest;
+            let tails = match self.eat(',') {
+                true => self.over_ranges(body)?,
+                false => vec![(vec![], body(self)?)],
+            };
+            if value <= high {
+                results.extend(tails.into_iter().map(|(tail, result)| {
+                    let assignment = std::iter::once((index.clone(), value)).chain(tail);
+                    (assignment.collect(), result)
+                }));
+            }
+        }
+        self.skipping = skipping;
+        match previous {
+            Some(value) => self.context.indices.insert(index, value),
+            None => self.context.indices.remove(&index),
+        };
+        Ok(results)
+    }
+
+    /// Reads ranges separated by commas, returning every value of their indices
+    fn assignments(&mut self) -> Result<Vec<Assignment>, ParseError> {
+        let results = self.over_ranges(&mut |_| Ok(()))?;
+        Ok(results
+            .into_iter()
+            .map(|(assignment, _)| assignment)
+            .collect())
+    }
+
+    /// Reads a sum, after its keyword: the term following the ranges is read again for each
+    /// value of the indices
+    fn sum(&mut self) -> Result<LinearFunction, ParseError> {
+        self.eat('(');
+        let terms = self.over_ranges(&mut |parser| match parser.eat(')') {
+            true => parser.term(),
+            false => Err(parser.unexpected(&["`,`", "`)`"])),
+        })?;
+        Ok(terms
+            .into_iter()
+            .map(|(_, term)| term)
+            .fold(LinearFunction::zero(), |a, b| a + b))
+    }
+
+    /// Reads a number, or a table of tables and numbers between brackets
+    fn table(&mut self) -> Result<Table, ParseError> {
+        if !self.eat('[') {
+            return Ok(Table::Number(self.constant()?));
+        }
+        let mut rows = vec![];
+        if self.eat(']') {
+            return Ok(Table::Rows(rows));
+        }
+        loop {
+            rows.push(self.table()?);
+            if self.eat(']') {
+                return Ok(Table::Rows(rows));
+            }
+            if !self.eat(',') {
+                return Err(self.unexpected(&["`,`", "`]`"]));
+            }
+        }
+    }
+
+    /// Reads an expression that has to be constant
+    fn constant(&mut self) -> Result<Coefficient, ParseError> {
+        self.peek();
+        let start = self.position;
+        let value = self.expression()?;
+        if !value.is_constant() {
+            let written = self.text[start..self.position].trim();
+            return Err(self.error_since(start, format!("`{written}` isn't a number")));
+        }
+        Ok(value.constant)
+    }
+
+    /// Reads a number, with an optional fractional part and exponent
+    fn number(&mut self) -> Option<Coefficient> {
+        self.peek();
+        let rest = &self.text[self.position..];
+        let digits = |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+
+        let mut length = digits(rest);
+        // The dots of a range aren't a fractional part: 1..3
+        if rest[length..].starts_with('.') && !rest[length..].starts_with("..") {
+            length += 1 + digits(&rest[length + 1..]);
+        }
+        if length == 0 || rest[..length] == *"." {
+            return None;
+        }
+        // An exponent needs digits, otherwise the e is a variable: 2e is 2 * e
+        if let Some(exponent) = rest[length..].strip_prefix(['e', 'E']) {
+            let sign = usize::from(exponent.starts_with(['+', '-']));
+            let exponent_digits = digits(&exponent[sign..]);
+            if exponent_digits > 0 {
+                length += 1 + sign + exponent_digits;
+            }
+        }
+        self.position += length;
+        rest[..length].parse().ok()
+    }
+
+    /// Reads a name, a letter followed by letters, digits and underscores
+    fn variable(&mut self) -> Option<&'a str> {
+        self.peek();
+        let rest = &self.text[self.position..];
+        if !rest.starts_with(|c: char| c.is_alphabetic()) {
+            return None;
+        }
+        let length = rest
+            .find(|c: char| !c.is_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        self.position += length;
+        Some(&rest[..length])
+    }
+
+    /// Multiplies two factors of a term, one of which has to be constant
+    fn multiply(
+        &self,
+        left: LinearFunction,
+        right: LinearFunction,
+        start: usize,
+    ) -> Result<LinearFunction, ParseError> {
+        if self.skipping {
+            Ok(LinearFunction::zero())
+        } else if left.is_constant() {
+            Ok(right * left.constant)
+        } else if right.is_constant() {
+            Ok(left * right.constant)
+        } else {
+            let term = self.text[start..self.position].trim();
+            Err(self.error_since(
+                start,
+                format!("`{term}` isn't linear, variables can only be multiplied by constants"),
+            ))
+        }
+    }
+
+    /// Divides a term by a factor, which has to be a constant other than 0
+    fn divide(
+        &self,
+        dividend: LinearFunction,
+        divisor: LinearFunction,
+        start: usize,
+    ) -> Result<LinearFunction, ParseError> {
+        let term = self.text[start..self.position].trim();
+        if self.skipping {
+            Ok(LinearFunction::zero())
+        } else if !divisor.is_constant() {
+            Err(self.error_since(
+                start,
+                format!("`{term}` isn't linear, variables can only be divided by constants"),
+            ))
+        } else if divisor.constant == 0.0 {
+            Err(self.error_since(start, format!("`{term}` divides by zero")))
+        } else {
+            Ok(dividend / divisor.constant)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn function(constant: Coefficient, coefficients: &[(&str, Coefficient)]) -> LinearFunction {
+        let coefficients = coefficients
+            .iter()
+            .map(|(var, coeff)| (var.to_string(), *coeff))
+            .collect::<HashMap<_, _>>();
+        LinearFunction::new(constant, coefficients)
+    }
+
+    #[test]
+    fn test_grammar() {
+        let parse = |text| Parser::parse(text).unwrap();
+        assert_eq!(parse("2(x + y)"), function(0.0, &[("x", 2.0), ("y", 2.0)]));
+        assert_eq!(parse("3*4x"), function(0.0, &[("x", 12.0)]));
+        assert_eq!(parse("x/2"), function(0.0, &[("x", 0.5)]));
+        assert_eq!(parse("1/4 y + 1"), function(1.0, &[("y", 0.25)]));
+        assert_eq!(parse("-(x - y)"), function(0.0, &[("x", -1.0), ("y", 1.0)]));
+        assert_eq!(parse("1.5e2x - 2E-1"), function(-0.2, &[("x", 150.0)]));
+        assert_eq!(parse("2e"), function(0.0, &[("e", 2.0)]));
+        assert_eq!(parse("x * (2 - 3) / -4"), function(0.0, &[("x", 0.25)]));
+        assert_eq!(
+            parse("3 x0+ 2   y"),
+            function(0.0, &[("x0", 3.0), ("y", 2.0)])
+        );
+    }
+
+    #[test]
+    fn test_sums() {
+        let context = Context {
+            parameters: HashMap::from([
+                ("n".to_string(), Table::Number(3.0)),
+                (
+                    "a".to_string(),
+                    Table::Rows(vec![Table::Number(2.0), Table::Number(5.0)]),
+                ),
+            ]),
+            indices: HashMap::from([("i".to_string(), 2)]),
+        };
+        let parse = |text| Parser::parse_with(text, context.clone()).unwrap();
+        assert_eq!(parse("x[i][1] + a[i]"), function(5.0, &[("x_2_1", 1.0)]));
+        assert_eq!(
+            parse("sum(j in 1..n) j y[j] + 1"),
+            function(1.0, &[("y_1", 1.0), ("y_2", 2.0), ("y_3", 3.0)])
+        );
+        assert_eq!(
+            parse("sum(j in 1..2, k in j..2) z[j][k]"),
+            function(0.0, &[("z_1_1", 1.0), ("z_1_2", 1.0), ("z_2_2", 1.0)])
+        );
+        assert_eq!(
+            parse("sum(j in 3..2) y[j] - x_1"),
+            function(0.0, &[("x_1", -1.0)])
+        );
+        // The term of an empty sum isn't evaluated, so a[j] doesn't have to exist
+        assert_eq!(
+            parse("sum(j in 4..3) a[j] y[j] / a[j - 4] + x"),
+            function(0.0, &[("x", 1.0)])
+        );
+        assert_eq!(
+            parse("sum(j in 1..0, k in j/2..n) a[k]"),
+            LinearFunction::zero()
+        );
+
+        let error = Parser::parse_with("x[i/4]", context.clone()).unwrap_err();
+        assert_eq!(error.message, "`i/4` isn't an integer");
+        let error = Parser::parse_with("sum(j 1..2) y[j]", context.clone()).unwrap_err();
+        assert_eq!(error.expected, vec!["`in`"]);
+        let error = Parser::parse_with("sum(j in 1..1000000000) y", context.clone()).unwrap_err();
+        assert_eq!((error.column, error.length), (4, 18));
+        assert_eq!(error.message, "ranges can't take more than 100000 values");
+    }
+
+    #[test]
+    fn test_nonlinear() {
+        let error = Parser::parse("2 + 3x*y").unwrap_err();
+        assert_eq!((error.column, error.length), (4, 4));
+        assert_eq!(
+            error.message,
+            "`3x*y` isn't linear, variables can only be multiplied by constants"
+        );
+
+        let error = Parser::parse("x / (y + 1)").unwrap_err();
+        assert_eq!((error.column, error.length), (0, 11));
+        assert_eq!(
+            Parser::parse("x / (2 - 2)").unwrap_err().message,
+            "`x / (2 - 2)` divides by zero"
+        );
+
+        let error = Parser::parse("2(x + y").unwrap_err();
+        assert_eq!(error.expected, vec!["`)`"]);
+        assert_eq!(error.message, "unexpected end of expression");
+    }
+}
@@ -0,0 +1,282 @@
+//! Canonical printing of numbers, linear functions and comparisons. With the default format,
+//! every number is written exactly, so that the text is parsed back to the same program
+use crate::constraint::Operator;
+use crate::linear_function::{Coefficient, LinearFunction, Variable};
+use itertools::Itertools;
+
+/// Largest denominator written when printing numbers as fractions
+const MAX_DENOMINATOR: u16 = 100;
+
+/// Order of the variables in a linear function
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TermOrder {
+    /// Alphabetical order of the names
+    Alphabetical,
+    /// Alphabetical order, numbers in the names being compared by value: `x2` before `x10`
+    #[default]
+    Natural,
+}
+
+/// How programs are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Format {
+    /// Digits after the decimal point, trailing zeros removed. None writes numbers exactly
+    pub precision: Option<usize>,
+    /// Numbers that are exactly a ratio of small integers are written as fractions, like `1/3`
+    pub fractions: bool,
+    pub order: TermOrder,
+    /// The constant comes before the variables rather than after them
+    pub constant_first: bool,
+    /// The operators of consecutive constraints are aligned in columns
+    pub align: bool,
+    /// Coefficients are separated from their variable by a space, as in `2 x`
+    pub spaced: bool,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format {
+            precision: None,
+            fractions: false,
+            order: TermOrder::default(),
+            constant_first: true,
+            align: false,
+            spaced: false,
+        }
+    }
+}
+
+/// Splits a name into runs of digits, read as numbers, and runs of other characters
+pub(crate) fn natural_key(var: &Variable) -> Vec<(String, u64)> {
+    var.chars()
+        .group_by(char::is_ascii_digit)
+        .into_iter()
+        .map(|(digits, run)| {
+            let run: String = run.collect();
+            match digits {
+                true => (String::new(), run.parse().unwrap_or(u64::MAX)),
+                false => (run, 0),
+            }
+        })
+        .collect()
+}
+
+/// Returns a number as the smallest fraction equal to it, if any
+fn fraction(value: Coefficient) -> Option<(i64, u16)> {
+    (1..=MAX_DENOMINATOR).find_map(|denominator| {
+        let numerator = (value * denominator as Coefficient).round();
+        (numerator / denominator as Coefficient == value).then_some((numerator as i64, denominator))
+    })
+}
+
+impl Format {
+    /// Returns the value a number is written as
+    fn rounded(&self, value: Coefficient) -> Coefficient {
+        match self.precision {
+            Some(_) if self.fractions && fraction(value).is_some() => value,
+            Some(precision) => {
+                let scale = (10.0 as Coefficient).powi(precision as i32);
+                (value * scale).round() / scale
+            }
+            None => value,
+        }
+    }
+
+    /// Writes a number
+    /// ```rust
+    /// use simplex::format::Format;
+    /// let exact = Format::default();
+    /// assert_eq!(exact.number(0.25), "0.25");
+    /// let rounded = Format { precision: Some(1), ..Format::default() };
+    /// assert_eq!(rounded.number(0.25), "0.3");
+    /// assert_eq!(rounded.number(2.0), "2");
+    /// let fractions = Format { fractions: true, ..Format::default() };
+    /// assert_eq!(fractions.number(-1.0 / 3.0), "-1/3");
+    /// ```
+    pub fn number(&self, value: Coefficient) -> String {
+        match fraction(value) {
+            Some((numerator, denominator)) if self.fractions && denominator > 1 => {
+                return format!("{numerator}/{denominator}");
+            }
+            _ => {}
+        }
+        let value = self.rounded(value);
+        let written = match self.precision {
+            Some(precision) => format!("{value:.precision$}"),
+            None => format!("{value}"),
+        };
+        let written = match written.contains('.') {
+            true => written.trim_end_matches('0').trim_end_matches('.'),
+            false => &written,
+        };
+        match written {
+            "-0" => "0".to_string(),
+            written => written.to_string(),
+        }
+    }
+
+    /// Writes a term, a number or a variable along with its coefficient, both positive
+    fn term(&self, var: Option<&Variable>, magnitude: Coefficient) -> String {
+        let number = self.number(magnitude);
+        match var {
+            None => number,
+            Some(var) if self.rounded(magnitude) == 1.0 => var.clone(),
+            // A space keeps `2 e1` from being read as 2e1, and fractions readable
+            Some(var) if self.spaced || number.contains('/') || var.starts_with(['e', 'E']) => {
+                format!("{number} {var}")
+            }
+            Some(var) => format!("{number}{var}"),
+        }
+    }
+
+    /// Writes a linear function. Terms written as 0 are left out
+    /// ```rust
+    /// use simplex::format::{Format, TermOrder};
+    /// use simplex::linear_function::LinearFunction;
+    /// let function: LinearFunction = "x10 - 0.25x2 + 1/3 y + 4".parse().unwrap();
+    /// assert_eq!(Format::default().function(&function), "4 - 0.25x2 + x10 + 0.33333334y");
+    /// let format = Format {
+    ///     fractions: true,
+    ///     order: TermOrder::Alphabetical,
+    ///     constant_first: false,
+    ///     ..Format::default()
+    /// };
+    /// assert_eq!(format.function(&function), "x10 - 1/4 x2 + 1/3 y + 4");
+    /// ```
+    pub fn function(&self, function: &LinearFunction) -> String {
+        let mut variables: Vec<&Variable> = function.var_iter().collect();
+        match self.order {
+            TermOrder::Alphabetical => variables.sort(),
+            TermOrder::Natural => variables.sort_by_key(|var| (natural_key(var), *var)),
+        }
+        let constant = std::iter::once((None, function.constant));
+        let variables = variables.into_iter().map(|var| (Some(var), function[var]));
+        let terms: Vec<_> = match self.constant_first {
+            true => constant.chain(variables).collect(),
+            false => variables.chain(constant).collect(),
+        };
+
+        let mut written = String::new();
+        for (var, coefficient) in terms {
+            if self.rounded(coefficient) == 0.0 {
+                continue;
+            }
+            let term = self.term(var, coefficient.abs());
+            written += &match (written.is_empty(), coefficient < 0.0) {
+                (true, false) => term,
+                (true, true) => format!("-{term}"),
+                (false, false) => format!(" + {term}"),
+                (false, true) => format!(" - {term}"),
+            };
+        }
+        match written.is_empty() {
+            true => "0".to_string(),
+            false => written,
+        }
+    }
+
+    /// Writes the sides and operators of a chain of comparisons, alternating
+    pub fn columns(&self, sides: &[LinearFunction], operators: &[Operator]) -> Vec<String> {
+        let sides = sides.iter().map(|side| self.function(side));
+        let operators = operators.iter().map(Operator::to_string);
+        sides.interleave(operators).collect()
+    }
+
+    /// Writes a chain of comparisons, each operator between two sides
+    pub fn comparison(&self, sides: &[LinearFunction], operators: &[Operator]) -> String {
+        self.columns(sides, operators).join(" ")
+    }
+
+    /// Writes consecutive rows, aligned if asked to
+    pub fn rows(&self, rows: &[Row]) -> Vec<String> {
+        match self.align {
+            true => align(rows),
+            false => rows.iter().map(Row::to_string).collect(),
+        }
+    }
+}
+
+/// A comparison being written, its columns padded when aligned with others
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Row {
+    pub indent: String,
+    /// Name prefix, with its colon
+    pub name: String,
+    /// Sides and operators, alternating
+    pub columns: Vec<String>,
+    /// Comment at the end of the line, with its hash
+    pub comment: String,
+}
+
+impl std::fmt::Display for Row {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.indent)?;
+        if !self.name.is_empty() {
+            write!(f, "{} ", self.name)?;
+        }
+        write!(f, "{}", self.columns.join(" "))?;
+        if !self.comment.is_empty() {
+            write!(f, "  {}", self.comment)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes rows whose names and columns are padded to the same width
+/// ```rust
+/// use simplex::format::{align, Row};
+/// let row = |name: &str, columns: &[&str]| Row {
+///     indent: "  ".to_string(),
+///     name: name.to_string(),
+///     columns: columns.iter().map(|c| c.to_string()).collect(),
+///     comment: String::new(),
+/// };
+/// let rows = [row("c1:", &["x + y", "<=", "4"]), row("", &["2x", ">=", "1"])];
+/// assert_eq!(align(&rows), vec!["  c1: x + y <= 4", "      2x    >= 1"]);
+/// ```
+pub fn align(rows: &[Row]) -> Vec<String> {
+    let width = |text: &String| text.chars().count();
+    // Rows are indented like the least indented of them
+    let indent = rows
+        .iter()
+        .map(|row| &row.indent)
+        .min_by_key(|indent| width(indent));
+    let name_width = rows.iter().map(|row| width(&row.name)).max().unwrap_or(0);
+    let columns = rows.iter().map(|row| row.columns.len()).max().unwrap_or(0);
+    // The last column of each row isn't padded
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            let cells = rows.iter().filter(|row| row.columns.len() > i + 1);
+            cells.map(|row| width(&row.columns[i])).max().unwrap_or(0)
+        })
+        .collect();
+
+    rows.iter()
+        .map(|row| {
+            let name = match name_width {
+                0 => String::new(),
+                _ => format!("{:name_width$} ", row.name),
+            };
+            let last = row.columns.len().saturating_sub(1);
+            let columns = row
+                .columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| match i == last {
+                    true => column.clone(),
+                    // Operators are aligned on their right, like `=` under `<=`
+                    false if i % 2 == 1 => format!("{column:>width$}", width = widths[i]),
+                    false => format!("{column:width$}", width = widths[i]),
+                });
+            let comment = match row.comment.is_empty() {
+                true => String::new(),
+                false => format!("  {}", row.comment),
+            };
+            format!(
+                "{}{name}{}{comment}",
+                indent.unwrap_or(&row.indent),
+                columns.collect::<Vec<_>>().join(" ")
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,601 @@
+//! 3D convex hull (quickhull) of the vertices of a polyhedron
+//!
+//! The hull is made of triangles wound counter-clockwise when seen from the outside,
+//! so that back faces can be culled, with one normal per face for lighting.
+use std::collections::HashMap;
+
+pub type Point = [f32; 3];
+
+/// A triangle of the hull, given as indices into the vertex list
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Face {
+    pub vertices: [usize; 3],
+    pub normal: Point,
+}
+
+/// A convex polyhedron, centered around (0, 0, 0) and scaled to fit in the [-1, 1] cube
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Polyhedron {
+    pub vertices: Vec<Point>,
+    pub faces: Vec<Face>,
+    center: Point,
+    scale: f32,
+}
+
+impl Polyhedron {
+    /// Builds the convex hull of a set of points, given in the program's coordinates
+    pub fn from_points(points: &[Point]) -> Polyhedron {
+        let mut center = [0f32; 3];
+        let mut max_value = 0f32;
+        for point in points {
+            for (c, v) in center.iter_mut().zip(point) {
+                *c += v;
+                max_value = max_value.max(v.abs());
+            }
+        }
+        if !points.is_empty() {
+            center.iter_mut().for_each(|c| *c /= points.len() as f32);
+        }
+
+        let mut polyhedron = Polyhedron {
+            vertices: vec![],
+            faces: vec![],
+            center,
+            scale: if max_value > 0.0 { max_value } else { 1.0 },
+        };
+        polyhedron.vertices = points.iter().map(|p| polyhedron.normalize(p)).collect();
+        polyhedron.faces = quickhull(&polyhedron.vertices);
+        polyhedron
+    }
+
+    /// Applies the centering and scaling of the polyhedron to a point of the program.
+    /// Missing coordinates are taken as 0, extra coordinates are ignored
+    pub fn normalize(&self, point: &[f32]) -> Point {
+        let mut normalized = [0f32; 3];
+        for (i, dest) in normalized.iter_mut().enumerate() {
+            let value = point.get(i).copied().unwrap_or(0.0);
+            *dest = (value - self.center[i]) / self.scale;
+        }
+        normalized
+    }
+
+    /// Gives the coordinates in the program of a point of the polyhedron
+    pub fn denormalize(&self, point: Point) -> Point {
+        [0, 1, 2].map(|i| point[i] * self.scale + self.center[i])
+    }
+
+    /// Expresses the plane a.x + c = 0 of the program in the coordinates of the polyhedron
+    pub fn normalize_plane(&self, a: Point, c: f32) -> (Point, f32) {
+        let offset = a.iter().zip(&self.center).map(|(a, m)| a * m).sum::<f32>() + c;
+        (a.map(|a| a * self.scale), offset)
+    }
+
+    /// Returns the smallest box containing the vertices of the polyhedron
+    pub fn bounds(&self) -> (Point, Point) {
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for vertex in &self.vertices {
+            for i in 0..3 {
+                min[i] = min[i].min(vertex[i]);
+                max[i] = max[i].max(vertex[i]);
+            }
+        }
+        if self.vertices.is_empty() {
+            ([0.0; 3], [0.0; 3])
+        } else {
+            (min, max)
+        }
+    }
+
+    /// Returns the indices of the faces lying on the plane a.x + c = 0
+    pub fn faces_on_plane(&self, a: Point, c: f32) -> Vec<usize> {
+        let norm = a.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm == 0.0 {
+            return vec![];
+        }
+        let on_plane =
+            |p: &Point| (a.iter().zip(p).map(|(a, x)| a * x).sum::<f32>() + c).abs() <= 1e-4 * norm;
+        (0..self.faces.len())
+            .filter(|&f| {
+                self.faces[f]
+                    .vertices
+                    .iter()
+                    .all(|&i| on_plane(&self.vertices[i]))
+            })
+            .collect()
+    }
+
+    /// Returns the index of the first face hit by a ray, if any (Möller–Trumbore)
+    pub fn ray_cast(&self, origin: Point, direction: Point) -> Option<usize> {
+        let origin = origin.map(f64::from);
+        let direction = direction.map(f64::from);
+        self.faces
+            .iter()
+            .enumerate()
+            .filter_map(|(f, face)| {
+                let [a, b, c] = face.vertices.map(|i| self.vertices[i].map(f64::from));
+                let (ab, ac) = (sub(&b, &a), sub(&c, &a));
+                let p = cross(&direction, &ac);
+                let determinant = dot(&ab, &p);
+                if determinant.abs() < 1e-12 {
+                    return None;
+                }
+                let to_origin = sub(&origin, &a);
+                let u = dot(&to_origin, &p) / determinant;
+                let q = cross(&to_origin, &ab);
+                let v = dot(&direction, &q) / determinant;
+                let t = dot(&ac, &q) / determinant;
+                (u >= 0.0 && v >= 0.0 && u + v <= 1.0 && t > 0.0).then_some((f, t))
+            })
+            .min_by(|(_, t1), (_, t2)| t1.total_cmp(t2))
+            .map(|(f, _)| f)
+    }
+
+    /// Returns the triangles of the hull as interleaved (position, normal) vertices,
+    /// ready to be uploaded to a vertex buffer
+    pub fn triangles(&self) -> Vec<[f32; 6]> {
+        self.faces
+            .iter()
+            .flat_map(|face| {
+                face.vertices.map(|i| {
+                    let [x, y, z] = self.vertices[i];
+                    let [nx, ny, nz] = face.normal;
+                    [x, y, z, nx, ny, nz]
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the edges of the polyhedron as pairs of vertex indices.
+    /// Edges between coplanar triangles are diagonals of a facet and are left out
+    pub fn edges(&self) -> Vec<[usize; 2]> {
+        let mut normals_by_edge: HashMap<(usize, usize), Vec<Point>> = HashMap::new();
+        for face in &self.faces {
+            let [a, b, c] = face.vertices;
+            for edge in [(a, b), (b, c), (c, a)] {
+                normals_by_edge.entry(edge).or_default().push(face.normal);
+            }
+        }
+
+        // An edge is a diagonal if every face on one side has a coplanar face on the other side
+        let coplanar =
+            |[x, y, z]: Point, [tx, ty, tz]: Point| x * tx + y * ty + z * tz >= 1.0 - 1e-4;
+        let matched = |normals: &[Point], twins: &[Point]| {
+            normals
+                .iter()
+                .all(|n| twins.iter().any(|t| coplanar(*n, *t)))
+        };
+        let no_twin = vec![];
+        normals_by_edge
+            .iter()
+            .filter_map(|(&(from, to), normals)| {
+                let twins = normals_by_edge.get(&(to, from)).unwrap_or(&no_twin);
+                // Each edge is seen in both directions, keep only one of them
+                let first_seen = from < to || twins.is_empty();
+                if first_seen && !(matched(normals, twins) && matched(twins, normals)) {
+                    Some([from.min(to), from.max(to)])
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+fn sub(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalized(a: [f64; 3]) -> [f64; 3] {
+    let norm = dot(&a, &a).sqrt();
+    if norm == 0.0 {
+        a
+    } else {
+        a.map(|v| v / norm)
+    }
+}
+
+struct HullFace {
+    vertices: [usize; 3],
+    normal: [f64; 3],
+    offset: f64,
+    outside: Vec<usize>,
+}
+
+impl HullFace {
+    fn new(vertices: [usize; 3], points: &[[f64; 3]]) -> HullFace {
+        let [a, b, c] = vertices.map(|i| points[i]);
+        let normal = normalized(cross(&sub(&b, &a), &sub(&c, &a)));
+        HullFace {
+            vertices,
+            normal,
+            offset: dot(&normal, &a),
+            outside: vec![],
+        }
+    }
+
+    fn distance(&self, point: &[f64; 3]) -> f64 {
+        dot(&self.normal, point) - self.offset
+    }
+
+    fn edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c] = self.vertices;
+        [(a, b), (b, c), (c, a)]
+    }
+}
+
+/// Computes the convex hull of a set of points using the quickhull algorithm.
+///
+/// Faces are wound counter-clockwise seen from the outside. Flat point sets produce
+/// both sides of their polygon, and sets of less than 3 non-aligned points produce no face
+pub fn quickhull(points: &[Point]) -> Vec<Face> {
+    let points: Vec<[f64; 3]> = points.iter().map(|p| p.map(f64::from)).collect();
+    if points.len() < 3 {
+        return vec![];
+    }
+
+    let extent = points
+        .iter()
+        .flat_map(|p| p.iter())
+        .fold(0f64, |acc, v| acc.max(v.abs()));
+    let eps = 1e-5 * extent.max(1.0);
+
+    // Initial simplex: two extreme points, the farthest point from their line,
+    // and the farthest point from their plane
+    let a = (0..points.len())
+        .min_by(|&i, &j| points[i][0].total_cmp(&points[j][0]))
+        .unwrap();
+    let b = (0..points.len())
+        .max_by(|&i, &j| {
+            let di = sub(&points[i], &points[a]);
+            let dj = sub(&points[j], &points[a]);
+            dot(&di, &di).total_cmp(&dot(&dj, &dj))
+        })
+        .unwrap();
+    let ab = sub(&points[b], &points[a]);
+    if dot(&ab, &ab).sqrt() <= eps {
+        return vec![];
+    }
+    let line_distance = |i: usize| {
+        let c = cross(&ab, &sub(&points[i], &points[a]));
+        dot(&c, &c).sqrt() / dot(&ab, &ab).sqrt()
+    };
+    let c = (0..points.len())
+        .max_by(|&i, &j| line_distance(i).total_cmp(&line_distance(j)))
+        .unwrap();
+    if line_distance(c) <= eps {
+        return vec![];
+    }
+    let plane = HullFace::new([a, b, c], &points);
+    let d = (0..points.len())
+        .max_by(|&i, &j| {
+            plane
+                .distance(&points[i])
+                .abs()
+                .total_cmp(&plane.distance(&points[j]).abs())
+        })
+        .unwrap();
+    if plane.distance(&points[d]).abs() <= eps {
+        return flat_hull(&points, plane.normal, eps);
+    }
+
+    let mut faces: Vec<Option<HullFace>> = if plane.distance(&points[d]) > 0.0 {
+        vec![[a, c, b], [a, b, d], [b, c, d], [c, a, d]]
+    } else {
+        vec![[a, b, c], [a, d, b], [b, d, c], [c, d, a]]
+    }
+    .into_iter()
+    .map(|vertices| Some(HullFace::new(vertices, &points)))
+    .collect();
+
+    // Assign every point to the first face it is in front of
+    let assign = |faces: &mut [Option<HullFace>], candidates: &[usize], indices: &[usize]| {
+        for &p in indices {
+            if let Some(face) = candidates
+                .iter()
+                .filter_map(|&f| faces[f].as_ref().map(|face| (f, face)))
+                .find(|(_, face)| face.distance(&points[p]) > eps)
+                .map(|(f, _)| f)
+            {
+                faces[face].as_mut().unwrap().outside.push(p);
+            }
+        }
+    };
+    let all_points: Vec<usize> = (0..points.len())
+        .filter(|p| ![a, b, c, d].contains(p))
+        .collect();
+    assign(&mut faces, &[0, 1, 2, 3], &all_points);
+
+    while let Some(face_index) = faces
+        .iter()
+        .position(|f| f.as_ref().is_some_and(|f| !f.outside.is_empty()))
+    {
+        // The eye point is the farthest point in front of the face
+        let face = faces[face_index].as_ref().unwrap();
+        let eye = *face
+            .outside
+            .iter()
+            .max_by(|&&i, &&j| {
+                face.distance(&points[i])
+                    .total_cmp(&face.distance(&points[j]))
+            })
+            .unwrap();
+
+        let visible: Vec<usize> = faces
+            .iter()
+            .enumerate()
+            .filter_map(|(i, f)| match f {
+                Some(f) if f.distance(&points[eye]) > eps => Some(i),
+                _ => None,
+            })
+            .collect();
+
+        // The horizon is made of the edges of visible faces whose twin isn't visible
+        let visible_edges: Vec<(usize, usize)> = visible
+            .iter()
+            .flat_map(|&f| faces[f].as_ref().unwrap().edges())
+            .collect();
+        let horizon = visible_edges
+            .iter()
+            .filter(|(from, to)| !visible_edges.contains(&(*to, *from)));
+
+        let mut orphans = vec![];
+        for &f in &visible {
+            orphans.extend(faces[f].take().unwrap().outside);
+        }
+        orphans.retain(|&p| p != eye);
+
+        let mut new_faces = vec![];
+        for &(from, to) in horizon {
+            new_faces.push(faces.len());
+            faces.push(Some(HullFace::new([from, to, eye], &points)));
+        }
+        assign(&mut faces, &new_faces, &orphans);
+    }
+
+    faces
+        .into_iter()
+        .flatten()
+        .map(|face| Face {
+            vertices: face.vertices,
+            normal: face.normal.map(|v| v as f32),
+        })
+        .collect()
+}
+
+/// Returns the polygon where the plane a.x + c = 0 cuts a box, in order around its center.
+/// The polygon is empty when the plane misses the box
+pub fn plane_section(a: Point, c: f32, min: Point, max: Point) -> Vec<Point> {
+    let value = |p: &Point| a.iter().zip(p).map(|(a, x)| a * x).sum::<f32>() + c;
+    let corner = |i: usize| {
+        [0, 1, 2].map(|axis| {
+            if i >> axis & 1 == 0 {
+                min[axis]
+            } else {
+                max[axis]
+            }
+        })
+    };
+
+    // Intersect the plane with the 12 edges of the box, which join corners differing by one axis
+    let mut points: Vec<Point> = vec![];
+    for from in 0..8 {
+        for axis in 0..3 {
+            let to = from | 1 << axis;
+            if to == from {
+                continue;
+            }
+            let (p, q) = (corner(from), corner(to));
+            let (value_p, value_q) = (value(&p), value(&q));
+            let crossing = if value_p == 0.0 {
+                Some(p)
+            } else if value_p * value_q < 0.0 {
+                let t = value_p / (value_p - value_q);
+                Some([0, 1, 2].map(|i| p[i] + t * (q[i] - p[i])))
+            } else {
+                None
+            };
+            if let Some(point) = crossing {
+                if !points.iter().any(|known| known == &point) {
+                    points.push(point);
+                }
+            }
+        }
+    }
+    if points.len() < 3 {
+        return vec![];
+    }
+
+    // Sort the points by angle around their center, in a basis of the plane
+    let normal = normalized(a.map(f64::from));
+    let helper = if normal[0].abs() < 0.9 {
+        [1.0, 0.0, 0.0]
+    } else {
+        [0.0, 1.0, 0.0]
+    };
+    let u = normalized(cross(&normal, &helper));
+    let v = cross(&normal, &u);
+    let count = points.len() as f64;
+    let center = [0, 1, 2].map(|i| points.iter().map(|p| p[i] as f64).sum::<f64>() / count);
+    let angle = |p: &Point| {
+        let d = sub(&p.map(f64::from), &center);
+        dot(&d, &v).atan2(dot(&d, &u))
+    };
+    points.sort_by(|p, q| angle(p).total_cmp(&angle(q)));
+    points
+}
+
+/// Hull of coplanar points: a fan of the 2D convex hull, on both sides of the plane
+fn flat_hull(points: &[[f64; 3]], normal: [f64; 3], eps: f64) -> Vec<Face> {
+    // Build a basis of the plane
+    let helper = if normal[0].abs() < 0.9 {
+        [1.0, 0.0, 0.0]
+    } else {
+        [0.0, 1.0, 0.0]
+    };
+    let u = normalized(cross(&normal, &helper));
+    let v = cross(&normal, &u);
+    let projected: Vec<(f64, f64)> = points.iter().map(|p| (dot(p, &u), dot(p, &v))).collect();
+
+    // Monotone chain, giving a counter-clockwise polygon in the (u, v) basis
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&i, &j| {
+        projected[i]
+            .0
+            .total_cmp(&projected[j].0)
+            .then(projected[i].1.total_cmp(&projected[j].1))
+    });
+    let turn = |o: usize, a: usize, b: usize| {
+        let (o, a, b) = (projected[o], projected[a], projected[b]);
+        (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+    };
+    let mut polygon: Vec<usize> = vec![];
+    for pass in [order.clone(), order.into_iter().rev().collect()] {
+        let start = polygon.len();
+        for i in pass {
+            while polygon.len() >= start + 2
+                && turn(polygon[polygon.len() - 2], polygon[polygon.len() - 1], i) <= eps * eps
+            {
+                polygon.pop();
+            }
+            polygon.push(i);
+        }
+        polygon.pop();
+    }
+
+    let front = normal.map(|v| v as f32);
+    let back = front.map(|v| -v);
+    (1..polygon.len().saturating_sub(1))
+        .flat_map(|i| {
+            let (a, b, c) = (polygon[0], polygon[i], polygon[i + 1]);
+            [
+                Face {
+                    vertices: [a, b, c],
+                    normal: front,
+                },
+                Face {
+                    vertices: [a, c, b],
+                    normal: back,
+                },
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube() -> Vec<Point> {
+        let mut points = vec![];
+        for x in [0.0, 1.0] {
+            for y in [0.0, 1.0] {
+                for z in [0.0, 1.0] {
+                    points.push([x, y, z]);
+                }
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn test_cube_hull() {
+        let mut points = cube();
+        points.push([0.5, 0.5, 0.5]);
+        let faces = quickhull(&points);
+
+        assert_eq!(faces.len(), 12);
+        assert!(faces.iter().all(|f| !f.vertices.contains(&8)));
+    }
+
+    #[test]
+    fn test_hull_winding() {
+        let points = cube();
+        let center = [0.5f32; 3];
+        for face in quickhull(&points) {
+            let [a, b, c] = face.vertices.map(|i| points[i].map(f64::from));
+            let winding = cross(&sub(&b, &a), &sub(&c, &a));
+            let outward = sub(&a, &center.map(f64::from));
+            assert!(dot(&winding, &outward) > 0.0);
+            assert!(dot(&face.normal.map(f64::from), &outward) > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_flat_hull() {
+        let points = vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.5, 0.5, 0.0],
+        ];
+        let faces = quickhull(&points);
+
+        assert_eq!(faces.len(), 4);
+    }
+
+    #[test]
+    fn test_edges() {
+        let cube = Polyhedron::from_points(&cube());
+        let square = Polyhedron::from_points(&[
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ]);
+
+        assert_eq!(cube.edges().len(), 12);
+        assert_eq!(square.edges().len(), 4);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let polyhedron = Polyhedron::from_points(&[
+            [0.0, 0.0, 0.0],
+            [4.0, 0.0, 0.0],
+            [0.0, 4.0, 0.0],
+            [0.0, 0.0, 4.0],
+        ]);
+
+        assert_eq!(polyhedron.normalize(&[1.0, 1.0, 1.0]), [0.0, 0.0, 0.0]);
+        assert_eq!(polyhedron.denormalize([0.75, -0.25, -0.25]), [4.0, 0.0, 0.0]);
+        assert_eq!(polyhedron.vertices[1], [0.75, -0.25, -0.25]);
+    }
+
+    #[test]
+    fn test_plane_section() {
+        // x + y + z = 1 cuts the unit cube along a triangle
+        let section = plane_section([1.0, 1.0, 1.0], -1.0, [0.0; 3], [1.0; 3]);
+        assert_eq!(section.len(), 3);
+        // z = 0.5 cuts it along a square
+        let section = plane_section([0.0, 0.0, 1.0], -0.5, [0.0; 3], [1.0; 3]);
+        assert_eq!(section.len(), 4);
+        assert!(section.iter().all(|p| p[2] == 0.5));
+        // x = 2 misses it
+        assert!(plane_section([1.0, 0.0, 0.0], -2.0, [0.0; 3], [1.0; 3]).is_empty());
+    }
+
+    #[test]
+    fn test_ray_cast() {
+        let cube = Polyhedron::from_points(&cube());
+        let face = cube
+            .ray_cast([0.1, 0.2, 5.0], [0.0, 0.0, -1.0])
+            .expect("the ray goes through the cube");
+        assert_eq!(cube.faces[face].normal, [0.0, 0.0, 1.0]);
+        assert!(cube.faces_on_plane([0.0, 0.0, 1.0], -0.5).contains(&face));
+        assert_eq!(cube.ray_cast([0.1, 0.2, 5.0], [0.0, 0.0, 1.0]), None);
+    }
+}
@@ -2,10 +2,25 @@
 //! definition of the simplex object
 
 pub mod app;
+mod camera;
+mod comparison;
 pub mod constraint;
+pub mod document;
+pub mod explanation;
+mod expression;
+pub mod format;
+pub mod hull;
 pub mod linear_function;
+mod lp;
+mod mps;
+mod plot;
 mod polyhedron;
-mod error;
+mod projection;
+mod rows;
+mod sensitivity;
+mod tableau;
+mod terminal;
+pub mod error;
 
 use crate::linear_function::{Coefficient, Variable};
 use constraint::Constraints;
@@ -13,6 +28,7 @@ use linear_function::LinearFunction;
 use std::collections::HashSet;
 use itertools::Itertools;
 use crate::error::SimplexError;
+use crate::explanation::{EnteringReason, StepExplanation};
 
 #[derive(Debug, Clone)]
 pub struct LinearProgram {
@@ -20,11 +36,45 @@ pub struct LinearProgram {
     pub constraints: Constraints,
 }
 
+/// Variables exchanged by a pivot between two consecutive states of the algorithm
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pivot {
+    pub entering: Variable,
+    pub leaving: Variable,
+}
+
+/// Rule choosing the variable entering the basis, among those with a positive coefficient
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PivotRule {
+    /// The first variable in alphabetical order, which never cycles
+    Bland,
+    /// The variable with the largest coefficient
+    Dantzig,
+    /// The variable whose edge improves the objective the most per unit of length
+    SteepestEdge,
+}
+
+impl PivotRule {
+    pub const ALL: [PivotRule; 3] = [PivotRule::Bland, PivotRule::Dantzig, PivotRule::SteepestEdge];
+}
+
+impl std::fmt::Display for PivotRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PivotRule::Bland => write!(f, "Bland"),
+            PivotRule::Dantzig => write!(f, "Dantzig"),
+            PivotRule::SteepestEdge => write!(f, "Steepest edge"),
+        }
+    }
+}
+
 /// Simplex object
 #[derive(Debug, Clone)]
 pub struct Simplex {
     index: usize,
     historic: Vec<LinearProgram>,
+    /// Why the entering variable of each pivot was chosen, reasons[i] leading to historic[i + 1]
+    reasons: Vec<EnteringReason>,
 }
 
 impl LinearProgram {
@@ -39,6 +89,89 @@ impl LinearProgram {
         self.constraints.is_valid()
     }
 
+    /// Returns the variable entering the basis according to a pivot rule, None if optimal.
+    /// Ties are broken in alphabetical order
+    pub fn entering_variable(&self, rule: PivotRule) -> Option<Variable> {
+        let coefficient = |var: &Variable| self.linear_function[var];
+        // Increasing a variable by 1 moves the basic variables by its coefficients
+        let edge_length = |var: &Variable| {
+            let squares: Coefficient = self.constraints.iter().map(|c| c.right[var].powi(2)).sum();
+            (1.0 + squares).sqrt()
+        };
+        let score = |var: &Variable| match rule {
+            PivotRule::Bland => 0.0,
+            PivotRule::Dantzig => coefficient(var),
+            PivotRule::SteepestEdge => coefficient(var) / edge_length(var),
+        };
+
+        self.linear_function
+            .var_iter()
+            .filter(|var| coefficient(var) > 0.0)
+            .sorted()
+            .fold(None, |best: Option<&Variable>, var| match best {
+                Some(best) if score(best) >= score(var) => Some(best),
+                _ => Some(var),
+            })
+            .cloned()
+    }
+
+    /// Returns a copy where gap variables are displayed under the name of their row, as
+    /// `slack(name)`. It is meant to be displayed, not solved
+    pub fn named(&self) -> LinearProgram {
+        LinearProgram {
+            linear_function: self.linear_function.renamed(|var| self.constraints.display_name(var)),
+            constraints: self.constraints.named(),
+        }
+    }
+
+    /// Reads a program written in the MPS format, see [document::Document::from_mps].
+    /// A minimized objective is negated
+    pub fn from_mps(text: &str) -> Result<LinearProgram, error::ParseError> {
+        document::Document::from_mps(text).map(|document| document.program().0)
+    }
+
+    /// Writes the program in the MPS format, each row `εi = c + a·x` becoming `c + a·x >= 0`
+    pub fn to_mps(&self) -> String {
+        document::Document::from(self).to_mps()
+    }
+
+    /// Reads a program written in the CPLEX LP format, see [document::Document::from_lp].
+    /// A minimized objective is negated
+    pub fn from_lp(text: &str) -> Result<LinearProgram, error::ParseError> {
+        document::Document::from_lp(text).map(|document| document.program().0)
+    }
+
+    /// Writes the program in the CPLEX LP format, each row `εi = c + a·x` becoming
+    /// `c + a·x >= 0`
+    pub fn to_lp(&self) -> String {
+        document::Document::from(self).to_lp()
+    }
+
+    /// Returns the basic variables, in the order of the constraints
+    pub fn basis(&self) -> Vec<Variable> {
+        self.constraints
+            .iter()
+            .filter_map(|c| c.left.name_single_variable())
+            .collect()
+    }
+
+    /// Returns the pivot that leads from this state to the next one
+    pub fn pivot_to(&self, next: &LinearProgram) -> Option<Pivot> {
+        let basis = self.basis();
+        let next_basis = next.basis();
+        let entering = next_basis.iter().find(|v| !basis.contains(v))?;
+        let leaving = basis.iter().find(|v| !next_basis.contains(v))?;
+        Some(Pivot {
+            entering: entering.clone(),
+            leaving: leaving.clone(),
+        })
+    }
+
+    /// Returns the value of the objective at the current point
+    pub fn objective_value(&self) -> Coefficient {
+        self.linear_function.constant
+    }
+
     pub fn is_unbounded(&self) -> bool {
         self.linear_function.var_iter()
             .any(|v| self.constraints.most_restrictive(v).is_none())
@@ -78,14 +211,49 @@ impl LinearProgram {
         var_set.into_iter().sorted().collect()
     }
 
-    fn out_of_base_variables(&self) -> Vec<Variable> {
+    /// Give every variable out of base, which can enter it, sorted by alphabetical order
+    pub fn out_of_base_variables(&self) -> Vec<Variable> {
         let mut variables = HashSet::new();
         for constraint in self.constraints.iter() {
             for var in constraint.right.var_iter() {
                 variables.insert(var);
             }
         }
-        variables.into_iter().cloned().collect()
+        variables.into_iter().cloned().sorted().collect()
+    }
+
+    /// Returns the state reached by pivoting on a variable out of base
+    pub fn pivoted(&self, var: &Variable) -> Result<LinearProgram, SimplexError> {
+        if !self.out_of_base_variables().contains(var) {
+            return Err(SimplexError::InvalidPivot);
+        }
+        let mut next = self.clone();
+        next.pivot(var.clone())?;
+        Ok(next)
+    }
+
+    /// Returns the state reached by bringing the variables of a basis into it, pivoting on
+    /// any row whose basic variable isn't part of that basis. The ratio test is skipped,
+    /// so the state reached may not be feasible
+    pub fn with_basis(&self, basis: &[Variable]) -> LinearProgram {
+        let mut state = self.clone();
+        for var in basis {
+            let row = state.constraints.iter().position(|c| {
+                c.right.contains(var)
+                    && c.left.name_single_variable().is_some_and(|basic| !basis.contains(&basic))
+            });
+            if let Some(row) = row {
+                state.constraints.pivot(row, var);
+                let right = state.constraints[row].right.clone();
+                state.linear_function.replace(var, &right);
+            }
+        }
+        state
+    }
+
+    /// Whether every basic variable is nonnegative, so that the current point is a vertex
+    pub fn is_feasible(&self) -> bool {
+        self.constraints.iter().all(|c| c.right.constant >= 0.0)
     }
 }
 
@@ -94,30 +262,135 @@ impl Simplex {
         self.index == 0
     }
 
-    pub fn next_step(&mut self, use_bland_rule: bool) -> Result<(), SimplexError> {
-        if let Some(var) = self
+    /// Goes to the next step, computing it if needed, and explains how it was reached
+    pub fn next_step(&mut self, use_bland_rule: bool) -> Result<StepExplanation, SimplexError> {
+        let var = self
             .current_state()
             .linear_function
-            .first_positive_coefficient(use_bland_rule)
-        {
+            .first_positive_coefficient(use_bland_rule);
+        let reason = if use_bland_rule {
+            EnteringReason::Bland
+        } else {
+            EnteringReason::FirstPositive
+        };
+        self.step_on(var, reason)
+    }
+
+    /// Goes to the next step, choosing the entering variable with a pivot rule
+    pub fn next_step_with_rule(&mut self, rule: PivotRule) -> Result<StepExplanation, SimplexError> {
+        let var = self.current_state().entering_variable(rule);
+        self.step_on(var, EnteringReason::from(rule))
+    }
+
+    /// Goes to the step reached by pivoting on a variable, or stops if there is none
+    fn step_on(&mut self, var: Option<Variable>, reason: EnteringReason) -> Result<StepExplanation, SimplexError> {
+        if let Some(var) = var {
+            // The step is only taken once it is known to be valid
             if self.index == self.historic.len() - 1 {
                 let mut new = self.current_state().clone();
                 new.pivot(var)?;
+                let explanation = StepExplanation::new(self.current_state(), &new, reason)
+                    .ok_or(SimplexError::InvalidPivot)?;
                 self.historic.push(new);
+                self.reasons.push(reason);
+                self.index += 1;
+                return Ok(explanation);
             }
+            let explanation = self.explanation(self.index + 1).ok_or(SimplexError::InvalidPivot)?;
             self.index += 1;
-            Ok(())
+            Ok(explanation)
         } else {
             Err(SimplexError::AlreadyOptimal)
         }
     }
 
+    /// Explains how a step of the historic was reached from the previous one,
+    /// with the rule that chose its entering variable
+    pub fn explanation(&self, step: usize) -> Option<StepExplanation> {
+        let previous = self.historic.get(step.checked_sub(1)?)?;
+        let next = self.historic.get(step)?;
+        let reason = *self.reasons.get(step - 1)?;
+        StepExplanation::new(previous, next, reason)
+    }
+
+    /// Pivots on a chosen entering variable instead of following the pivot rule.
+    /// The states computed after the current one are dropped if they differ from the new one
+    pub fn pivot_on(&mut self, var: &Variable) -> Result<(), SimplexError> {
+        let next = self.current_state().pivoted(var)?;
+        let already_computed = self
+            .historic
+            .get(self.index + 1)
+            .and_then(|computed| self.current_state().pivot_to(computed))
+            .is_some_and(|pivot| pivot.entering == *var);
+        if !already_computed {
+            self.historic.truncate(self.index + 1);
+            self.reasons.truncate(self.index);
+            self.historic.push(next);
+            self.reasons.push(EnteringReason::Chosen);
+        }
+        self.index += 1;
+        Ok(())
+    }
+
     pub fn previous_step(&mut self) {
         if !self.is_first_step() {
             self.index -= 1;
         }
     }
 
+    /// Computes every step up to the optimum, without changing the current step.
+    /// Stops early if the program turns out to be unbounded
+    pub fn compute_all_steps(&mut self, use_bland_rule: bool) {
+        let index = self.index;
+        self.index = self.historic.len() - 1;
+        while self.next_step(use_bland_rule).is_ok() {}
+        self.index = index;
+    }
+
+    /// Forgets the steps computed after the current one
+    pub fn forget_next_steps(&mut self) {
+        self.historic.truncate(self.index + 1);
+        self.reasons.truncate(self.index);
+    }
+
+    /// Goes to a step of the historic, or to the last step computed if it is further
+    pub fn go_to(&mut self, step: usize) {
+        self.index = step.min(self.historic.len() - 1);
+    }
+
+    /// Returns the index of the current step in the historic
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns every point visited by the algorithm so far, in order
+    pub fn historic_points(&self) -> Vec<Vec<f32>> {
+        self.historic.iter().map(|state| state.point()).collect()
+    }
+
+    /// Returns the optimal point of the program, without changing the current step
+    /// None if the program turns out to be unbounded
+    pub fn optimum(&self, use_bland_rule: bool) -> Option<Vec<f32>> {
+        let mut simplex = self.clone();
+        loop {
+            match simplex.next_step(use_bland_rule) {
+                Ok(_) => continue,
+                Err(SimplexError::AlreadyOptimal) => return Some(simplex.current_point()),
+                Err(_) => return None,
+            }
+        }
+    }
+
+    /// Returns every state computed so far, including the ones after the current step
+    pub fn historic(&self) -> &[LinearProgram] {
+        &self.historic
+    }
+
+    /// Returns a reference to the state the algorithm started from
+    pub fn initial_state(&self) -> &LinearProgram {
+        &self.historic[0]
+    }
+
     /// Returns a reference to the current state of the algorithm
     pub fn current_state(&self) -> &LinearProgram {
         &self.historic[self.index]
@@ -137,6 +410,7 @@ impl From<LinearProgram> for Simplex {
         Simplex {
             index: 0,
             historic: vec![value],
+            reasons: vec![],
         }
     }
 }
@@ -148,11 +422,30 @@ impl std::fmt::Display for LinearProgram {
     }
 }
 
+impl std::str::FromStr for LinearProgram {
+    type Err = error::ParseError;
+
+    /// Parses a whole document, see [document]. A minimized objective is negated
+    /// ```rust
+    /// use simplex::LinearProgram;
+    /// use simplex::document::Document;
+    ///
+    /// let program: LinearProgram = "max: x + 2y\nsubject to\n  x + y <= 4".parse().unwrap();
+    /// let text = Document::from(&program).to_string();
+    /// assert_eq!(text, "max: x + 2y\nsubject to\n  4 - x - y >= 0\n");
+    /// assert_eq!(text.parse::<LinearProgram>().unwrap().constraints, program.constraints);
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<document::Document>().map(|document| document.program().0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
+    #[allow(unused_variables)]
     fn test_non_gap_variables() {
         use std::str::FromStr;
         let lp = LinearProgram {
@@ -183,4 +476,126 @@ mod tests {
         simplex.next_step(true).unwrap();
         assert_eq!(simplex.current_point(), vec![200.0, 0.0]);
     }
+
+    #[test]
+    fn test_optimum() {
+        use std::str::FromStr;
+        let lp = LinearProgram {
+            linear_function: LinearFunction::from_str("x + 2y").unwrap(),
+            constraints: Constraints::compile("x + y <= 2\n x + 2y <= 3").unwrap(),
+        };
+        let simplex = Simplex::from(lp);
+        assert_eq!(simplex.optimum(true), Some(vec![1.0, 1.0]));
+        assert_eq!(simplex.index(), 0);
+        assert_eq!(simplex.historic_points(), vec![vec![0.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_pivot_to() {
+        use std::str::FromStr;
+        let lp = LinearProgram {
+            linear_function: LinearFunction::from_str("x + 2y").unwrap(),
+            constraints: Constraints::compile("x <= 200\n y <= 100").unwrap(),
+        };
+        let mut simplex = Simplex::from(lp);
+        simplex.next_step(true).unwrap();
+
+        let historic = simplex.historic();
+        assert_eq!(
+            historic[0].pivot_to(&historic[1]),
+            Some(Pivot {
+                entering: "x".to_string(),
+                leaving: "ε0".to_string(),
+            })
+        );
+        assert_eq!(historic[1].objective_value(), 200.0);
+    }
+
+    #[test]
+    fn test_pivot_on() {
+        use std::str::FromStr;
+        let lp = LinearProgram {
+            linear_function: LinearFunction::from_str("x + 2y").unwrap(),
+            constraints: Constraints::compile("x <= 200\n y <= 100").unwrap(),
+        };
+        let mut simplex = Simplex::from(lp);
+        simplex.next_step(true).unwrap();
+        simplex.previous_step();
+
+        // Choosing another entering variable replaces the step computed before
+        simplex.pivot_on(&"y".to_string()).unwrap();
+        assert_eq!(simplex.current_point(), vec![0.0, 100.0]);
+        assert_eq!(simplex.historic().len(), 2);
+        assert!(matches!(
+            simplex.pivot_on(&"y".to_string()),
+            Err(SimplexError::InvalidPivot)
+        ));
+        assert_eq!(simplex.index(), 1);
+    }
+
+    #[test]
+    fn test_compute_all_steps() {
+        use std::str::FromStr;
+        let lp = LinearProgram {
+            linear_function: LinearFunction::from_str("x + 2y").unwrap(),
+            constraints: Constraints::compile("x <= 200\n y <= 100").unwrap(),
+        };
+        let mut simplex = Simplex::from(lp);
+        simplex.compute_all_steps(true);
+        assert_eq!(simplex.index(), 0);
+        assert_eq!(simplex.historic().len(), 3);
+
+        simplex.go_to(5);
+        assert_eq!(simplex.current_point(), vec![200.0, 100.0]);
+    }
+
+    #[test]
+    fn test_explanation_reason() {
+        use std::str::FromStr;
+        let lp = LinearProgram {
+            linear_function: LinearFunction::from_str("x + 2y").unwrap(),
+            constraints: Constraints::compile("x <= 200\n y <= 100").unwrap(),
+        };
+        let mut simplex = Simplex::from(lp);
+        simplex.next_step_with_rule(PivotRule::Dantzig).unwrap();
+        assert_eq!(simplex.explanation(1).unwrap().reason, EnteringReason::Dantzig);
+        simplex.pivot_on(&"x".to_string()).unwrap();
+        assert_eq!(simplex.explanation(2).unwrap().reason, EnteringReason::Chosen);
+
+        simplex.go_to(0);
+        simplex.next_step(false).unwrap();
+        assert_eq!(simplex.index(), 1);
+        assert_eq!(simplex.explanation(1).unwrap().reason, EnteringReason::Dantzig);
+    }
+
+    #[test]
+    fn test_forget_next_steps() {
+        use std::str::FromStr;
+        let lp = LinearProgram {
+            linear_function: LinearFunction::from_str("x + 2y").unwrap(),
+            constraints: Constraints::compile("x <= 200\n y <= 100").unwrap(),
+        };
+        let mut simplex = Simplex::from(lp);
+        simplex.compute_all_steps(true);
+        assert_eq!(simplex.historic().len(), 3);
+        simplex.go_to(1);
+        simplex.forget_next_steps();
+        assert_eq!(simplex.historic().len(), 2);
+        assert!(simplex.explanation(1).is_some());
+        assert!(simplex.explanation(2).is_none());
+    }
+
+    #[test]
+    fn test_entering_variable() {
+        use std::str::FromStr;
+        let lp = LinearProgram {
+            linear_function: LinearFunction::from_str("x + 2y + 2z").unwrap(),
+            constraints: Constraints::compile("x <= 4\n 3y <= 9\n z <= 5").unwrap(),
+        };
+        assert_eq!(lp.entering_variable(PivotRule::Bland), Some("x".to_string()));
+        assert_eq!(lp.entering_variable(PivotRule::Dantzig), Some("y".to_string()));
+        // Increasing y moves ε1 three times as fast, so the edge along y is longer and
+        // improves the objective less per unit of length than the one along z
+        assert_eq!(lp.entering_variable(PivotRule::SteepestEdge), Some("z".to_string()));
+    }
 }
@@ -1,12 +1,7 @@
-use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::character::complete::{alpha1, alphanumeric0, multispace0};
-use std::collections::{HashMap, HashSet};
-
-use nom::multi::many0;
-use nom::number::complete::float;
-use nom::sequence::preceded;
-use nom::IResult;
+use std::collections::HashMap;
+use crate::error::ParseError;
+use crate::format::Format;
+use crate::expression::Parser;
 
 pub type Variable = String;
 pub type Coefficient = f32;
@@ -48,6 +43,11 @@ impl LinearFunction {
         }
     }
 
+    /// Returns true if no variable has a non-zero coefficient
+    pub fn is_constant(&self) -> bool {
+        self.coefficients.values().all(|coeff| *coeff == 0.0)
+    }
+
     /// Returns true if this function contains the given variable (i.e it has a non-zero coefficient)
     pub fn contains(&self, var: &Variable) -> bool {
         if let Some(coeff) = self.coefficients.get(var) {
@@ -115,6 +115,16 @@ impl LinearFunction {
         }
     }
 
+    /// Returns a copy where each variable is renamed, to be displayed.
+    /// Variables given the same name add up
+    pub fn renamed(&self, name: impl Fn(&Variable) -> String) -> LinearFunction {
+        let mut coefficients = HashMap::new();
+        for (var, coeff) in &self.coefficients {
+            *coefficients.entry(name(var)).or_insert(0.0) += coeff;
+        }
+        LinearFunction::new(self.constant, coefficients)
+    }
+
     /// Returns an iterator over the variables that have a coefficient different of 0
     pub fn var_iter(&self) -> impl Iterator<Item = &Variable> {
         self.coefficients.keys().filter(|var| self[var] != 0.0)
@@ -140,6 +150,7 @@ impl LinearFunction {
             && self.constant == 0.0
     }
 
+    #[allow(clippy::iter_kv_map)]
     pub fn non_gap_variables(&self) -> Vec<Variable> {
         self.coefficients
             .iter()
@@ -353,7 +364,7 @@ impl std::ops::Neg for LinearFunction {
 PARSE FUNCTIONS
  */
 impl std::str::FromStr for LinearFunction {
-    type Err = ();
+    type Err = ParseError;
 
     /// ```rust
     /// use std::collections::HashMap;
@@ -363,112 +374,14 @@ impl std::str::FromStr for LinearFunction {
     /// assert_eq!("3 - 2x".parse::<LinearFunction>().unwrap(), expected)
     /// ```
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        fn parse_variable(input: &str) -> IResult<&str, (Variable, Coefficient)> {
-            let (rest, positive) = if let Ok((rest, sign)) =
-                preceded(multispace0::<&str, ()>, alt((tag("-"), tag("+"))))(input)
-            {
-                (rest, sign == "+")
-            } else {
-                (input, true)
-            };
-
-            let mut found_coeff = false;
-            let (rest, coeff) =
-                if let Ok((rest, coeff)) = preceded(multispace0::<&str, ()>, float)(rest) {
-                    found_coeff = true;
-                    (rest, coeff)
-                } else {
-                    (rest, 1.0)
-                };
-
-            let rest = match preceded(multispace0::<&str, ()>, tag("*"))(rest) {
-                Ok((rest_mult, _)) => rest_mult,
-                _ => rest,
-            };
-
-            let (rest, variable) = match preceded(multispace0::<&str, ()>, alpha1)(rest) {
-                Ok((rest, variable)) => {
-                    let (rest, variable) = match alphanumeric0::<&str, ()>(rest) {
-                        Ok((rest, end_of_var)) => {
-                            let mut var = variable.to_owned();
-                            var += end_of_var;
-                            (rest, var)
-                        }
-                        _ => {
-                            return Err(nom::Err::Error(nom::error::Error {
-                                input: "aled",
-                                code: nom::error::ErrorKind::Fail,
-                            }))
-                        }
-                    };
-                    (rest, variable)
-                }
-                Err(_) if found_coeff => (rest, "".to_string()),
-                _ => {
-                    return Err(nom::Err::Error(nom::error::Error {
-                        input: "aled",
-                        code: nom::error::ErrorKind::Fail,
-                    }))
-                }
-            };
-
-            Ok((
-                rest,
-                (variable, if positive { coeff } else { -coeff }),
-            ))
-        }
-
-        let mut linear_func = LinearFunction::zero();
-        let (_, variables) = many0(parse_variable)(s).unwrap();
-        for (var, coeff) in variables {
-            if var.is_empty() {
-                linear_func.constant += coeff;
-            } else {
-                linear_func[&var] += coeff;
-            }
-        }
-        Ok(linear_func)
+        Parser::parse(s)
     }
 }
 
 impl std::fmt::Display for LinearFunction {
+    /// Writes every number exactly, see [crate::format]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // sort the hashmap by variable name
-        // filtre for the non-zero coefficients
-        // then iterate over the coefficients
-        let mut h_map: Vec<_> = self.coefficients.clone().into_iter().collect();
-        h_map.sort_by_key(|(var, _)| var.clone());
-        h_map.retain(|(_, coeff)| *coeff != 0.0);
-        let mut coeff_iter = h_map.iter();
-
-        if self.constant != 0.0 {
-            write!(f, "{:.1}", self.constant)
-        } else if let Some((var, coeff)) = coeff_iter.next() {
-            match *coeff {
-                x if x == 1.0 => write!(f, "{var}"),
-                x if x == -1.0 => write!(f, "-{var}"),
-                _ => write!(f, "{coeff:.1}{var}"),
-            }
-        } else {
-            write!(f, "0")
-        }?;
-        for (var, coeff) in coeff_iter {
-            match *coeff {
-                x if x == 1.0 => write!(f, " + {var}"),
-                x if x == -1.0 => write!(f, " - {var}"),
-                _ => write!(
-                    f,
-                    "{}{:.1}{var}",
-                    if coeff.is_sign_positive() {
-                        " + "
-                    } else {
-                        " - "
-                    },
-                    coeff.abs(),
-                ),
-            }?;
-        }
-        Ok(())
+        f.write_str(&Format::default().function(self))
     }
 }
 
@@ -525,6 +438,25 @@ mod tests {
         assert_eq!(lf1.name_single_variable().unwrap(), "x".to_string());
         assert_eq!(lf2.name_single_variable().unwrap(), "y".to_string());
     }
+
+    #[test]
+    fn test_renamed() {
+        let lf = LinearFunction::from_str("1 + 2x + 3y").unwrap();
+        let renamed = lf.renamed(|_| "z".to_string());
+        assert_eq!(renamed, LinearFunction::from_str("1 + 5z").unwrap());
+    }
+    #[test]
+    fn test_parse_error() {
+        let error = LinearFunction::from_str("3x + $y").unwrap_err();
+        assert_eq!((error.line, error.column, error.length), (0, 5, 1));
+        assert_eq!(error.message, "unexpected `$`");
+        assert_eq!(error.expected, vec!["a number", "a variable", "`(`"]);
+
+        let error = LinearFunction::from_str("x + 2y -").unwrap_err();
+        assert_eq!((error.column, error.length), (7, 1));
+        assert_eq!(error.message, "unexpected end of expression");
+    }
+
     #[test]
     fn test_variable_name_with_alphanumeric1() {
         let lf = LinearFunction::from_str("3 x0+ 2   y").unwrap();
@@ -0,0 +1,887 @@
+//! CPLEX LP format, a text format read and written by most solvers
+//!
+//! ```text
+//! \ Comments start with a backslash
+//! Maximize
+//!  profit: x + 2 y
+//! Subject To
+//!  c1: x + y
+//!      <= 4
+//!  c2: -2 <= x - y <= 2
+//! Bounds
+//!  -1 <= x <= 3
+//!  y free
+//! General
+//!  x
+//! End
+//! ```
+//! Keywords are case-insensitive, and statements can span several lines. Bounds replace the
+//! default bounds of their variable, which are 0 and infinity.
+//!
+//! Names are made of letters, digits and the symbols ``!"#$%&()/,.;?@_'{}|~``, and don't
+//! start with a digit or a period. Terms are a number followed by a variable, with no
+//! multiplication, sums or indices. Names the document can't parse, like `x(1)`, are changed
+//! into names it can, like `x_1_`
+use crate::constraint::{Constraint, Operator};
+use crate::document::{Document, NamedConstraint, Sense, SUBJECT_TO};
+use crate::error::ParseError;
+use crate::format::{natural_key, Format};
+use crate::linear_function::{Coefficient, LinearFunction, Variable};
+use crate::rows::{exported, flipped, imported, rows, split, Bounds, INFINITY};
+use std::collections::HashMap;
+
+/// Symbols that can be part of a name, along with letters and digits
+const NAME_SYMBOLS: &str = "!\"#$%&()/,.;?@_'{}|~";
+
+/// Spellings of the operators, longest first so that `<=` isn't read as `<`. Strict
+/// comparisons are read as large ones
+const OPERATORS: [(&str, Operator); 7] = [
+    ("<=", Operator::LessEqual),
+    ("=<", Operator::LessEqual),
+    (">=", Operator::GreaterEqual),
+    ("=>", Operator::GreaterEqual),
+    ("<", Operator::LessEqual),
+    (">", Operator::GreaterEqual),
+    ("=", Operator::Equal),
+];
+
+/// Keywords of the sections, the longest first when one starts another
+const SECTIONS: [(&str, Section); 20] = [
+    ("maximize", Section::Objective(Sense::Maximize)),
+    ("maximise", Section::Objective(Sense::Maximize)),
+    ("maximum", Section::Objective(Sense::Maximize)),
+    ("max", Section::Objective(Sense::Maximize)),
+    ("minimize", Section::Objective(Sense::Minimize)),
+    ("minimise", Section::Objective(Sense::Minimize)),
+    ("minimum", Section::Objective(Sense::Minimize)),
+    ("min", Section::Objective(Sense::Minimize)),
+    ("bounds", Section::Bounds),
+    ("bound", Section::Bounds),
+    ("generals", Section::General),
+    ("general", Section::General),
+    ("gen", Section::General),
+    ("binaries", Section::Binary),
+    ("binary", Section::Binary),
+    ("bin", Section::Binary),
+    ("semi-continuous", Section::Semi),
+    ("semis", Section::Semi),
+    ("semi", Section::Semi),
+    ("end", Section::End),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Objective(Sense),
+    Constraints,
+    Bounds,
+    General,
+    Binary,
+    Semi,
+    End,
+}
+
+/// Part of a statement written on a line
+struct Piece<'a> {
+    line: usize,
+    /// Column of the part in its line, in characters
+    column: usize,
+    text: &'a str,
+}
+
+/// Statement written over one or several lines
+#[derive(Default)]
+struct Statement<'a> {
+    pieces: Vec<Piece<'a>>,
+}
+
+impl Statement<'_> {
+    /// Returns the text of the statement, its lines being separated by spaces
+    fn text(&self) -> String {
+        self.pieces
+            .iter()
+            .map(|piece| piece.text)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Whether the statement is a whole comparison, or declares a free variable
+    fn is_complete(&self) -> bool {
+        let text = self.text();
+        let after = text
+            .rfind(['<', '>', '='])
+            .map(|i| text[i..].trim_start_matches(['<', '>', '=']));
+        after.is_some_and(|after| !after.trim().is_empty())
+            || text
+                .split_whitespace()
+                .last()
+                .is_some_and(|word| word.eq_ignore_ascii_case("free"))
+    }
+
+    /// Moves an error on the text of the statement to the line it was written on
+    fn locate(&self, error: ParseError) -> ParseError {
+        let mut start = 0;
+        for (i, piece) in self.pieces.iter().enumerate() {
+            let length = piece.text.chars().count();
+            if error.column <= start + length || i + 1 == self.pieces.len() {
+                let column = piece.column + error.column.saturating_sub(start);
+                return ParseError {
+                    line: piece.line,
+                    column,
+                    ..error
+                };
+            }
+            start += length + 1;
+        }
+        error
+    }
+}
+
+/// Token of a statement
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(Coefficient),
+    Name(String),
+    Plus,
+    Minus,
+    Operator(Operator),
+}
+
+/// Whether a character can be part of a name
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || NAME_SYMBOLS.contains(c)
+}
+
+/// Whether a word is read as a keyword, and can't name anything
+fn is_keyword(word: &str) -> bool {
+    let word = word.to_lowercase();
+    SECTIONS.iter().any(|(keyword, _)| *keyword == word)
+        || SUBJECT_TO.contains(&word.as_str())
+        || ["free", "inf", "infinity"].contains(&word.as_str())
+}
+
+/// Whether a word is a name of the format
+fn is_name(word: &str) -> bool {
+    word.starts_with(|c: char| is_name_char(c) && !c.is_ascii_digit() && c != '.')
+        && word.chars().all(is_name_char)
+}
+
+/// Returns the byte length of the number a text starts with, if any, its exponent included
+fn number_length(text: &str) -> Option<usize> {
+    let digits =
+        |text: &str| text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let mut length = digits(text);
+    if text[length..].starts_with('.') {
+        length += 1 + digits(&text[length + 1..]);
+    }
+    if length == 0 || &text[..length] == "." {
+        return None;
+    }
+    if text[length..].starts_with(['e', 'E']) {
+        let sign = text[length + 1..].starts_with(['+', '-']) as usize;
+        let exponent = digits(&text[length + 1 + sign..]);
+        if exponent > 0 {
+            length += 1 + sign + exponent;
+        }
+    }
+    Some(length)
+}
+
+/// Splits the text of a statement into tokens, along with their byte offsets
+fn tokens(text: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = vec![];
+    let mut offset = 0;
+    while let Some(c) = text[offset..].chars().next() {
+        let rest = &text[offset..];
+        let (length, token) = if c.is_whitespace() {
+            offset += c.len_utf8();
+            continue;
+        } else if c == '+' {
+            (1, Token::Plus)
+        } else if c == '-' {
+            (1, Token::Minus)
+        } else if let Some(&(spelling, operator)) = OPERATORS
+            .iter()
+            .find(|(spelling, _)| rest.starts_with(spelling))
+        {
+            (spelling.len(), Token::Operator(operator))
+        } else if let Some(length) = number_length(rest) {
+            let value = rest[..length]
+                .parse()
+                .map_err(|_| ParseError::unexpected(text, offset, &["a number"]))?;
+            (length, Token::Number(value))
+        } else if is_name(&c.to_string()) {
+            let length = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+            (length, Token::Name(rest[..length].to_string()))
+        } else {
+            return Err(ParseError::unexpected(text, offset, &[]));
+        };
+        tokens.push((offset, token));
+        offset += length;
+    }
+    Ok(tokens)
+}
+
+/// Tokens of a statement being read, variables getting names the document can parse
+struct Reader<'a> {
+    text: &'a str,
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(text: &'a str) -> Result<Self, ParseError> {
+        Ok(Reader {
+            text,
+            tokens: tokens(text)?,
+            position: 0,
+        })
+    }
+
+    /// Error on the current token, or at the end of the text
+    fn unexpected(&self, expected: &[&str]) -> ParseError {
+        let offset = self
+            .tokens
+            .get(self.position)
+            .map_or(self.text.len(), |&(offset, _)| offset);
+        ParseError::unexpected(self.text, offset, expected)
+    }
+
+    /// Fails unless every token was read
+    fn end(&self, expected: &[&str]) -> Result<(), ParseError> {
+        match self.position < self.tokens.len() {
+            true => Err(self.unexpected(expected)),
+            false => Ok(()),
+        }
+    }
+
+    /// Reads a sum of terms, each one being signed but the first
+    fn function(&mut self) -> Result<LinearFunction, ParseError> {
+        let mut function = LinearFunction::zero();
+        let mut first = true;
+        loop {
+            let mut sign = None;
+            while let Some((_, token @ (Token::Plus | Token::Minus))) =
+                self.tokens.get(self.position)
+            {
+                let flip = if *token == Token::Minus { -1.0 } else { 1.0 };
+                sign = Some(sign.unwrap_or(1.0) * flip);
+                self.position += 1;
+            }
+            if !first && sign.is_none() {
+                return Ok(function);
+            }
+            let sign = sign.unwrap_or(1.0);
+            let coefficient = match self.tokens.get(self.position) {
+                Some((_, Token::Number(value))) => {
+                    self.position += 1;
+                    Some(*value)
+                }
+                _ => None,
+            };
+            match (self.tokens.get(self.position), coefficient) {
+                (Some((_, Token::Name(name))), _) => {
+                    function[name] += sign * coefficient.unwrap_or(1.0);
+                    self.position += 1;
+                }
+                (_, Some(value)) => function.constant += sign * value,
+                (_, None) => return Err(self.unexpected(&["a number", "a variable"])),
+            }
+            first = false;
+        }
+    }
+
+    /// Reads comparisons, possibly chained as in `-5 <= x - y <= 5`
+    fn comparisons(&mut self) -> Result<Vec<Constraint>, ParseError> {
+        let mut constraints = vec![];
+        let mut left = self.function()?;
+        while let Some(&(offset, Token::Operator(operator))) = self.tokens.get(self.position) {
+            if let Some(previous) = constraints.last().map(|c: &Constraint| c.operator) {
+                if previous != operator || operator == Operator::Equal {
+                    let rest = &self.text[offset..];
+                    let spelling = OPERATORS
+                        .iter()
+                        .find(|(spelling, _)| rest.starts_with(spelling));
+                    return Err(ParseError {
+                        line: 0,
+                        column: self.text[..offset].chars().count(),
+                        length: spelling.map_or(1, |(spelling, _)| spelling.len()),
+                        expected: vec![],
+                        message: "a range is written as `lo <= x + y <= hi`".to_string(),
+                    });
+                }
+            }
+            self.position += 1;
+            let right = self.function()?;
+            constraints.push(Constraint::new(left, operator, right.clone()));
+            left = right;
+        }
+        self.end(&["`+`", "`-`", "`<=`", "`>=`", "`=`"])?;
+        if constraints.is_empty() {
+            return Err(ParseError {
+                line: 0,
+                column: 0,
+                length: self.text.chars().count(),
+                expected: ["<=", ">=", "="].iter().map(|op| op.to_string()).collect(),
+                message: "missing comparison operator".to_string(),
+            });
+        }
+        Ok(constraints)
+    }
+}
+
+/// Returns the section started by a line, and the byte offset of the rest of the line
+fn section(code: &str) -> Option<(Section, usize)> {
+    let start = code.len() - code.trim_start().len();
+    let lowercase = code[start..].to_ascii_lowercase();
+    let words: Vec<&str> = lowercase.split_whitespace().collect();
+    // The two words of `subject to` can be separated by several spaces
+    if words.len() >= 2 && SUBJECT_TO.contains(&words[..2].join(" ").as_str()) {
+        let second = lowercase.find(words[1]).unwrap_or(0) + words[1].len();
+        return Some((Section::Constraints, start + second));
+    }
+    let first = *words.first()?;
+    if SUBJECT_TO.contains(&first) {
+        return Some((Section::Constraints, start + first.len()));
+    }
+    SECTIONS
+        .iter()
+        .find(|(keyword, _)| *keyword == first)
+        .map(|&(keyword, section)| (section, start + keyword.len()))
+}
+
+/// Whether a variable stands for infinity
+fn is_infinity(var: &Variable) -> bool {
+    var.eq_ignore_ascii_case("inf") || var.eq_ignore_ascii_case("infinity")
+}
+
+/// Parses the name prefix of a statement, if any, and the byte offset of the rest
+fn named(text: &str) -> Result<(Option<String>, usize), ParseError> {
+    let Some(colon) = text.find(':') else {
+        return Ok((None, 0));
+    };
+    let name = text[..colon].trim();
+    if !is_name(name) {
+        let start = text.len() - text.trim_start().len();
+        return Err(ParseError::unexpected(text, start, &["a name"]));
+    }
+    Ok((Some(name.to_string()), colon + 1))
+}
+
+/// Reads the bounds given by a statement of the `Bounds` section
+fn read_bounds(text: &str, bounds: &mut Vec<(Variable, Bounds)>) -> Result<(), ParseError> {
+    let mut bound = |var: Variable, change: &dyn Fn(&mut Bounds)| match bounds
+        .iter_mut()
+        .find(|(v, _)| *v == var)
+    {
+        Some((_, bounds)) => change(bounds),
+        None => {
+            let mut new = Bounds::default();
+            change(&mut new);
+            bounds.push((var, new));
+        }
+    };
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if let [var, free] = words[..] {
+        if free.eq_ignore_ascii_case("free") {
+            if !is_name(var) {
+                let start = text.find(var).unwrap_or(0);
+                return Err(ParseError::unexpected(text, start, &["a variable"]));
+            }
+            bound(var.to_string(), &|bounds| {
+                *bounds = Bounds {
+                    lower: None,
+                    upper: None,
+                }
+            });
+            return Ok(());
+        }
+    }
+
+    for constraint in Reader::new(text)?.comparisons()? {
+        let (mut function, operator, rhs) = split(&constraint);
+        // Infinity is read as a variable, and gives no bound
+        let mut value = rhs;
+        let infinity = function.var_iter().find(|var| is_infinity(var)).cloned();
+        if let Some(infinity) = infinity {
+            value = -function[&infinity].signum() * Coefficient::INFINITY;
+            function[&infinity] = 0.0;
+        }
+        let variables: Vec<&Variable> = function.var_iter().collect();
+        let [var] = variables[..] else {
+            let start = text.len() - text.trim_start().len();
+            return Err(ParseError {
+                line: 0,
+                column: text[..start].chars().count(),
+                length: text.trim().chars().count(),
+                expected: vec![],
+                message: "a bound applies to a single variable".to_string(),
+            });
+        };
+        let coefficient = function[var];
+        let value = value / coefficient;
+        let value = (value.abs() < INFINITY).then_some(value);
+        let operator = match coefficient < 0.0 {
+            true => flipped(operator),
+            false => operator,
+        };
+        bound(var.clone(), &|bounds| match operator {
+            Operator::GreaterEqual => bounds.lower = value,
+            Operator::LessEqual => bounds.upper = value,
+            _ => (bounds.lower, bounds.upper) = (value, value),
+        });
+    }
+    Ok(())
+}
+
+impl Document {
+    /// Reads a program written in the CPLEX LP format
+    /// ```rust
+    /// use simplex::document::{Document, Sense};
+    /// let text = "\
+    /// \\ Diet
+    /// Minimize
+    ///  cost: 2 x + 3 y
+    /// Subject To
+    ///  demand: x + y >= 10
+    /// Bounds
+    ///  x <= 4
+    /// End";
+    /// let document = Document::from_lp(text).unwrap();
+    /// assert_eq!(document.sense, Sense::Minimize);
+    /// assert_eq!(
+    ///     document.to_string(),
+    ///     "min: 2x + 3y\nsubject to\n  demand: x + y >= 10\nbounds\n  x <= 4\n"
+    /// );
+    /// ```
+    pub fn from_lp(text: &str) -> Result<Document, ParseError> {
+        let mut document = Document::default();
+        let mut objective_line = None;
+        let mut statements: Vec<(Section, Statement)> = vec![];
+        let mut bounds = vec![];
+        // Line of each constraint name, which names a single statement
+        let mut name_lines: HashMap<String, usize> = HashMap::new();
+
+        // Lines are gathered into statements
+        for (i, line) in text.lines().enumerate() {
+            let code = line.split('\\').next().unwrap_or_default();
+            let (start, new_section) = match section(code) {
+                Some((Section::Semi, _)) => {
+                    return Err(ParseError {
+                        line: i,
+                        column: 0,
+                        length: code.trim_end().chars().count(),
+                        expected: vec![],
+                        message: "semi-continuous variables aren't supported".to_string(),
+                    });
+                }
+                Some((Section::End, _)) => break,
+                Some((next, start)) => (start, Some(next)),
+                None => (0, None),
+            };
+            if let Some(next) = new_section {
+                if let (Section::Objective(_), Some(first)) = (next, objective_line) {
+                    let keyword = code.len() - code.trim_start().len();
+                    return Err(ParseError {
+                        line: i,
+                        column: code[..keyword].chars().count(),
+                        length: code[keyword..start].chars().count(),
+                        expected: vec![],
+                        message: format!("the objective is already given on line {}", first + 1),
+                    });
+                }
+                if let Section::Objective(_) = next {
+                    objective_line = Some(i);
+                }
+                statements.push((next, Statement::default()));
+            }
+            let rest = code[start..].trim_end();
+            let text = rest.trim_start();
+            if text.is_empty() {
+                continue;
+            }
+            let Some((current, statement)) = statements.last_mut() else {
+                let expected = ["Maximize", "Minimize"];
+                return Err(ParseError::unexpected(
+                    code,
+                    start + rest.len() - text.len(),
+                    &expected,
+                )
+                .on_line(i));
+            };
+            // A name or a new comparison starts a new statement
+            let starts_new =
+                text.contains(':') || statement.is_complete() && !text.starts_with(['<', '>', '=']);
+            if !matches!(current, Section::Objective(_))
+                && !statement.pieces.is_empty()
+                && starts_new
+            {
+                let current = *current;
+                statements.push((current, Statement::default()));
+            }
+            let column = code[..start + rest.len() - text.len()].chars().count();
+            let piece = Piece {
+                line: i,
+                column,
+                text,
+            };
+            statements.last_mut().unwrap().1.pieces.push(piece);
+        }
+
+        for (section, statement) in &statements {
+            let text = statement.text();
+            let locate = |error: ParseError| statement.locate(error);
+            match section {
+                Section::Objective(sense) => {
+                    document.sense = *sense;
+                    let (_, start) = named(&text).map_err(locate)?;
+                    let mut reader =
+                        Reader::new(&text[start..]).map_err(|e| locate(e.after(&text[..start])))?;
+                    if !reader.tokens.is_empty() {
+                        document.objective = (reader.function())
+                            .and_then(|objective| reader.end(&["`+`", "`-`"]).map(|_| objective))
+                            .map_err(|e| locate(e.after(&text[..start])))?;
+                    }
+                }
+                _ if text.is_empty() => {}
+                Section::Constraints => {
+                    let (name, start) = named(&text).map_err(locate)?;
+                    let line = statement.pieces[0].line;
+                    if let Some(name) = &name {
+                        if let Some(first) = name_lines.insert(name.clone(), line) {
+                            let offset = text.len() - text.trim_start().len();
+                            return Err(locate(ParseError {
+                                line: 0,
+                                column: text[..offset].chars().count(),
+                                length: name.chars().count(),
+                                expected: vec![],
+                                message: format!(
+                                    "`{name}` already names a constraint on line {}",
+                                    first + 1
+                                ),
+                            }));
+                        }
+                    }
+                    let chain = Reader::new(&text[start..])
+                        .and_then(|mut reader| reader.comparisons())
+                        .map_err(|e| locate(e.after(&text[..start])))?;
+                    for constraint in chain {
+                        let name = name.clone();
+                        document
+                            .constraints
+                            .push(NamedConstraint { name, constraint });
+                    }
+                }
+                Section::Bounds => read_bounds(&text, &mut bounds).map_err(locate)?,
+                Section::General | Section::Binary => {
+                    for piece in &statement.pieces {
+                        let mut offset = 0;
+                        for word in piece.text.split_whitespace() {
+                            offset += piece.text[offset..].find(word).unwrap_or(0);
+                            if !is_name(word) {
+                                let error =
+                                    ParseError::unexpected(piece.text, offset, &["a variable"]);
+                                return Err(ParseError {
+                                    line: piece.line,
+                                    column: piece.column + error.column,
+                                    ..error
+                                });
+                            }
+                            let var = word.to_string();
+                            if !document.integers.contains(&var) {
+                                document.integers.push(var.clone());
+                            }
+                            if *section == Section::Binary {
+                                let binary = Bounds {
+                                    lower: Some(0.0),
+                                    upper: Some(1.0),
+                                };
+                                bounds.retain(|(other, _)| *other != var);
+                                bounds.push((var, binary));
+                            }
+                            offset += word.len();
+                        }
+                    }
+                }
+                Section::Semi | Section::End => unreachable!(),
+            }
+        }
+
+        if objective_line.is_none() {
+            return Err(ParseError {
+                line: 0,
+                column: 0,
+                length: 0,
+                expected: vec![],
+                message: "missing objective, such as `Maximize x + y`".to_string(),
+            });
+        }
+        for (var, bounds) in bounds {
+            document.add_bounds(&var, bounds);
+        }
+        // Names are changed once they are all read, so that those left as they are keep them
+        Ok(imported(&document, "x"))
+    }
+
+    /// Writes the document in the CPLEX LP format. Unnamed constraints are named after their
+    /// index, and a range written as `c: lo <= x + y <= hi` is written as such. Names are
+    /// written as in the MPS format, see [Document::to_mps], keywords ending with an underscore
+    pub fn to_lp(&self) -> String {
+        let document = exported(self, is_keyword);
+        let format = Format {
+            constant_first: false,
+            spaced: true,
+            ..Format::default()
+        };
+        let (rows, objective) = rows(&document);
+
+        let mut lines = vec![match document.sense {
+            Sense::Maximize => "Maximize".to_string(),
+            Sense::Minimize => "Minimize".to_string(),
+        }];
+        lines.push(format!(
+            " {objective}: {}",
+            format.function(&document.objective)
+        ));
+        lines.push("Subject To".to_string());
+        for row in &rows {
+            let function = format.function(&row.function);
+            lines.push(match row.range {
+                Some(range) => {
+                    let (lower, upper) = (row.rhs, row.rhs + range);
+                    let (lower, upper) = (format.number(lower), format.number(upper));
+                    format!(" {}: {lower} <= {function} <= {upper}", row.name)
+                }
+                None => format!(
+                    " {}: {function} {} {}",
+                    row.name,
+                    row.operator,
+                    format.number(row.rhs)
+                ),
+            });
+        }
+
+        let bounds = document.variable_bounds();
+        let mut variables: Vec<&Variable> = bounds.keys().collect();
+        variables.sort_by_key(|var| (natural_key(var), *var));
+        let bound_lines: Vec<String> = variables
+            .into_iter()
+            .filter_map(|var| {
+                let Bounds { lower, upper } = bounds[var];
+                let number = |value| format.number(value);
+                Some(match (lower, upper) {
+                    (None, None) => format!(" {var} free"),
+                    (Some(lower), Some(upper)) if lower == upper => {
+                        format!(" {var} = {}", number(lower))
+                    }
+                    (None, Some(upper)) => format!(" -inf <= {var} <= {}", number(upper)),
+                    (Some(0.0), None) => return None,
+                    (Some(lower), None) => format!(" {var} >= {}", number(lower)),
+                    (Some(0.0), Some(upper)) => format!(" {var} <= {}", number(upper)),
+                    (Some(lower), Some(upper)) => {
+                        format!(" {} <= {var} <= {}", number(lower), number(upper))
+                    }
+                })
+            })
+            .collect();
+        if !bound_lines.is_empty() {
+            lines.push("Bounds".to_string());
+            lines.extend(bound_lines);
+        }
+        if !document.integers.is_empty() {
+            lines.push("General".to_string());
+            lines.push(format!(" {}", document.integers.join(" ")));
+        }
+        lines.push("End".to_string());
+        lines.into_iter().map(|line| line + "\n").collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LinearProgram;
+
+    const EXAMPLE: &str = "\
+\\ Problem written by another solver
+MAXIMIZE
+ profit: x1 + 2 x2 + 3 x3
+     + x4
+Subject  To
+ c1: - x1 + x2 + x3 + 10 x4 <= 20
+ c2: x1 - 3 x2 + x3
+     <= 30
+ c3: x2 - 3.5 x4 = 0
+ -5 <= x1 - x2 <= 5
+Bounds
+ 0 <= x1 <= 40
+ 2 <= x4 <= 3
+ x3 >= -Inf
+ x2 <= +infinity
+General
+ x4
+Binary
+ b
+End
+anything after the end";
+
+    #[test]
+    fn test_read() {
+        let document = Document::from_lp(EXAMPLE).unwrap();
+        assert_eq!(document.sense, Sense::Maximize);
+        assert_eq!(document.objective.to_string(), "x1 + 2x2 + 3x3 + x4");
+        let constraints: Vec<String> = (document.constraints.iter())
+            .map(|c| format!("{}: {}", c.name.as_deref().unwrap_or("-"), c.constraint))
+            .collect();
+        assert_eq!(
+            constraints,
+            vec![
+                "c1: -x1 + x2 + x3 + 10x4 <= 20",
+                "c2: x1 - 3x2 + x3 <= 30",
+                "c3: x2 - 3.5x4 = 0",
+                "-: -5 <= x1 - x2",
+                "-: x1 - x2 <= 5",
+            ]
+        );
+        assert_eq!(document.integers, vec!["x4", "b"]);
+        assert_eq!(document.free, vec!["x3"]);
+        let bounds: Vec<String> = document.bounds.iter().map(Constraint::to_string).collect();
+        assert_eq!(bounds, vec!["x1 <= 40", "x4 >= 2", "x4 <= 3", "b <= 1"]);
+    }
+
+    #[test]
+    fn test_write() {
+        let document = Document::from_lp(EXAMPLE).unwrap();
+        let written = document.to_lp();
+        assert!(written.starts_with("Maximize\n OBJ: x1 + 2 x2 + 3 x3 + x4\nSubject To\n"));
+        // Unnamed comparisons are written apart, even when they form a range
+        assert!(written.contains("\n R4: -x1 + x2 <= 5\n R5: x1 - x2 <= 5\n"));
+        assert!(written.contains("\nBounds\n b <= 1\n x1 <= 40\n x3 free\n 2 <= x4 <= 3\n"));
+        assert!(written.ends_with("General\n x4 b\nEnd\n"));
+
+        // Rows are named, and variables are gathered under their bounds
+        let read = Document::from_lp(&written).unwrap();
+        assert_eq!(read.constraints[3].name.as_deref(), Some("R4"));
+        assert_eq!(read.variable_bounds(), document.variable_bounds());
+        assert_eq!(read.to_lp(), written);
+
+        // Programs are written with their maximized objective
+        let program =
+            LinearProgram::from_lp("Minimize\n x - 2 y\nSubject To\n x + y <= 4").unwrap();
+        let written = program.to_lp();
+        assert_eq!(
+            written,
+            "Maximize\n OBJ: -x + 2 y\nSubject To\n R1: -x - y >= -4\nEnd\n"
+        );
+        let read = LinearProgram::from_lp(&written).unwrap();
+        assert_eq!(read.linear_function, program.linear_function);
+        assert!(read.constraints.iter().eq(program.constraints.iter()));
+
+        // Internal names are written as names of the format, and read back as them
+        let (document, _) = Document::parse("max: x + y\nx - y <= 1\nfree x").unwrap();
+        let program = document.program().0;
+        let written = program.to_lp();
+        assert_eq!(
+            written,
+            "Maximize\n OBJ: -x_neg + x_pos + y\nSubject To\n R1: x_neg - x_pos + y >= -1\nEnd\n"
+        );
+        let read = LinearProgram::from_lp(&written).unwrap();
+        let renamed = |function: &LinearFunction| {
+            function.renamed(|var| var.replace('⁺', "_pos").replace('⁻', "_neg"))
+        };
+        assert_eq!(read.linear_function, renamed(&program.linear_function));
+        let rows = read.constraints.iter().zip(program.constraints.iter());
+        assert!(rows
+            .into_iter()
+            .all(|(read, row)| read.right == renamed(&row.right)));
+
+        // Gap variables are written as slacks
+        let mut simplex = crate::Simplex::from(program);
+        simplex.next_step(true).unwrap();
+        assert!(simplex.current_state().to_lp().contains("slack0"));
+
+        // Keywords and other characters are left out
+        let (document, _) = Document::parse("max: free + bin\ncapé: free <= 1").unwrap();
+        assert_eq!(
+            document.to_lp(),
+            "Maximize\n OBJ: bin_ + free_\nSubject To\n cap_: free_ <= 1\nEnd\n"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |text: &str| Document::from_lp(text).unwrap_err();
+
+        let e = error("Maximize\n x + y\nSubject To\n c1: x + y\n     <= 4 +");
+        assert_eq!((e.line, e.column), (4, 10));
+
+        let e = error("Maximize\n x\nMinimize\n y");
+        assert_eq!((e.line, e.column, e.length), (2, 0, 8));
+        assert_eq!(e.message, "the objective is already given on line 1");
+
+        let e = error("x + y <= 4");
+        assert_eq!(e.expected, vec!["Maximize", "Minimize"]);
+
+        let e = error("Minimize\n x\nBounds\n x + y <= 3");
+        assert_eq!((e.line, e.column, e.length), (3, 1, 10));
+        assert_eq!(e.message, "a bound applies to a single variable");
+
+        let e = error("Minimize\n x\nGeneral\n x 2y");
+        assert_eq!((e.line, e.column, e.length), (3, 3, 2));
+
+        let e = error("Minimize\n x\nSemi-Continuous\n x");
+        assert_eq!(e.message, "semi-continuous variables aren't supported");
+
+        let e = error("Subject To\n x <= 1");
+        assert_eq!(e.message, "missing objective, such as `Maximize x + y`");
+
+        // Names don't hold indices, and terms aren't multiplied
+        let e = error("Maximize\n x[1]");
+        assert_eq!((e.line, e.column, e.length), (1, 2, 1));
+        assert_eq!(e.message, "unexpected `[`");
+
+        let e = error("Maximize\n 2 x y");
+        assert_eq!((e.line, e.column), (1, 5));
+        assert_eq!(e.expected, vec!["`+`", "`-`"]);
+
+        let e = error("Maximize\n x\nSubject To\n c: 2 x * y <= 3");
+        assert_eq!((e.line, e.column), (3, 8));
+
+        let e = error("Maximize\n x\nSubject To\n 0 <= x >= 1");
+        assert_eq!((e.line, e.column, e.length), (3, 8, 2));
+
+        let e = error("Maximize\n x\nSubject To\n c: x <= 1\n d: x <= 2\n c: x >= 0");
+        assert_eq!((e.line, e.column, e.length), (5, 1, 1));
+        assert_eq!(e.message, "`c` already names a constraint on line 4");
+    }
+
+    #[test]
+    fn test_names() {
+        let text = "\
+Maximize
+ x(1) + x(2) + 2x.1 - 1.5e1 y
+Subject To
+ c(1): x(1) + x(2) <= 4
+ c.2: x.1 + x_1 <= 3
+Bounds
+ x(2) free
+General
+ x.1
+End";
+        let document = Document::from_lp(text).unwrap();
+        assert_eq!(
+            document.to_string(),
+            "\
+max: x_1_ + 2x_1_v1 + x_2_ - 15y
+subject to
+  c_1_: x_1_ + x_2_ <= 4
+  c_2: x_1 + x_1_v1 <= 3
+int x_1_v1
+free x_2_
+"
+        );
+        // Names are changed so that the document can parse them
+        let (parsed, _) = Document::parse(&document.to_string()).unwrap();
+        assert_eq!(parsed, document);
+    }
+}
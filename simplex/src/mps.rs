@@ -0,0 +1,728 @@
+//! MPS format, in which most solvers read and write linear programs
+//!
+//! ```text
+//! NAME          EXAMPLE
+//! OBJSENSE
+//!     MAX
+//! ROWS
+//!  N  PROFIT
+//!  L  LIM1
+//!  G  LIM2
+//! COLUMNS
+//!     X         PROFIT    1            LIM1      1
+//!     MARKER              'MARKER'                 'INTORG'
+//!     Y         PROFIT    2            LIM2      1
+//!     MARKER              'MARKER'                 'INTEND'
+//! RHS
+//!     RHS       LIM1      4            LIM2      1
+//! RANGES
+//!     RNG       LIM1      2
+//! BOUNDS
+//!  UP BND       Y         3
+//! ENDATA
+//! ```
+//! Both the fixed format, whose fields are read at given columns, and the free format, whose
+//! fields are separated by spaces, are read. Rows are read as named constraints, a ranged row
+//! giving two of them, and bounds as the bounds of the document. The objective is minimized
+//! unless an `OBJSENSE` section says otherwise
+use crate::constraint::{Constraint, Operator};
+use crate::document::{Document, NamedConstraint, Sense, OBJECTIVES};
+use crate::error::ParseError;
+use crate::format::{natural_key, Format};
+use crate::linear_function::{Coefficient, LinearFunction, Variable};
+use crate::rows::{exported, imported, rows, Bounds, Row, INFINITY};
+use std::collections::HashMap;
+
+/// Byte ranges of the six fields of a line of the fixed format
+const FIXED_FIELDS: [(usize, usize); 6] = [(1, 3), (4, 12), (14, 22), (24, 36), (39, 47), (49, 61)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Name,
+    ObjSense,
+    Rows,
+    Columns,
+    Rhs,
+    Ranges,
+    Bounds,
+}
+
+/// Field of a line, with its byte offset
+type Field<'a> = (usize, &'a str);
+
+/// Fields of a line of the free format
+fn free_fields(line: &str) -> Vec<Field<'_>> {
+    line.split_whitespace()
+        .map(|field| (field.as_ptr() as usize - line.as_ptr() as usize, field))
+        .collect()
+}
+
+/// Fields of a line of the fixed format, which can contain spaces
+fn fixed_fields(line: &str) -> Vec<Field<'_>> {
+    FIXED_FIELDS
+        .iter()
+        .filter_map(|&(start, end)| {
+            let field = line.get(start..end.min(line.len()))?;
+            let trimmed = field.trim();
+            let offset = start + field.len() - field.trim_start().len();
+            (!trimmed.is_empty()).then_some((offset, trimmed))
+        })
+        .collect()
+}
+
+/// Whether a bound is given with a value
+fn has_value(kind: &str) -> bool {
+    matches!(kind, "UP" | "LO" | "FX" | "LI" | "UI")
+}
+
+/// Whether the fields of a line are as many as its section expects
+fn fits(section: Section, fields: &[Field]) -> bool {
+    match (section, fields) {
+        (Section::Name | Section::ObjSense, _) => !fields.is_empty(),
+        (Section::Rows, _) => fields.len() == 2,
+        (Section::Columns, _) => fields.len() == 3 || fields.len() == 5,
+        (Section::Rhs | Section::Ranges, _) => (2..=5).contains(&fields.len()),
+        (Section::Bounds, [(_, kind), ..]) if has_value(&kind.to_uppercase()) => {
+            fields.len() == 3 || fields.len() == 4
+        }
+        (Section::Bounds, _) => (2..=4).contains(&fields.len()),
+    }
+}
+
+/// Whether the fields of a line holding values are numbers, telling apart the fields of the free
+/// format from those of the fixed format when a name has spaces
+fn has_numbers(section: Section, fields: &[Field]) -> bool {
+    let values: Vec<&Field> = match section {
+        Section::Columns if fields[1].1 == "'MARKER'" => vec![],
+        Section::Columns => fields[2..].iter().step_by(2).collect(),
+        Section::Rhs | Section::Ranges => {
+            fields[fields.len() % 2 + 1..].iter().step_by(2).collect()
+        }
+        Section::Bounds if has_value(&fields[0].1.to_uppercase()) => {
+            fields.last().into_iter().collect()
+        }
+        _ => vec![],
+    };
+    values
+        .iter()
+        .all(|(_, value)| value.parse::<Coefficient>().is_ok())
+}
+
+/// Error on a field of a line
+fn error_on(line: &str, (offset, field): Field, message: String) -> ParseError {
+    ParseError {
+        line: 0,
+        column: line[..offset].chars().count(),
+        length: field.chars().count(),
+        expected: vec![],
+        message,
+    }
+}
+
+/// Reads the number of a field
+fn number(line: &str, field: Field) -> Result<Coefficient, ParseError> {
+    field
+        .1
+        .parse()
+        .map_err(|_| ParseError::unexpected(line, field.0, &["a number"]))
+}
+
+/// Writes the fields of a line at the columns of the fixed format. A field too long for its
+/// columns pushes the next ones, the line still being read in the free format
+fn fixed_line(fields: &[&str]) -> String {
+    let mut line = String::new();
+    for (&(start, _), field) in FIXED_FIELDS.iter().zip(fields) {
+        if field.is_empty() {
+            continue;
+        }
+        let width = line.chars().count();
+        line += &" ".repeat(if width < start { start - width } else { 1 });
+        line += field;
+    }
+    line
+}
+
+/// Writes the values of a column or of a set in rows, two per line
+fn entry_lines(first: &str, entries: &[(&String, Coefficient)]) -> Vec<String> {
+    let format = Format::default();
+    entries
+        .chunks(2)
+        .map(|pair| {
+            let mut fields = vec![String::new(), first.to_string()];
+            for (row, value) in pair {
+                fields.extend([row.to_string(), format.number(*value)]);
+            }
+            fixed_line(&fields.iter().map(String::as_str).collect::<Vec<_>>())
+        })
+        .collect()
+}
+
+impl Document {
+    /// Reads a program written in the MPS format, fixed or free. Names are changed into names
+    /// the document can parse, `LIM 1` being read as `LIM_1`
+    /// ```rust
+    /// use simplex::document::{Document, Sense};
+    /// let text = "\
+    /// ROWS
+    ///  N  COST
+    ///  G  DEMAND
+    /// COLUMNS
+    ///     X         COST      2            DEMAND    1
+    ///     Y         COST      3            DEMAND    1
+    /// RHS
+    ///     RHS       DEMAND    10
+    /// BOUNDS
+    ///  UP BND       X         4
+    /// ENDATA";
+    /// let document = Document::from_mps(text).unwrap();
+    /// assert_eq!(document.sense, Sense::Minimize);
+    /// assert_eq!(
+    ///     document.to_string(),
+    ///     "min: 2X + 3Y\nsubject to\n  DEMAND: X + Y >= 10\nbounds\n  X <= 4\n"
+    /// );
+    /// ```
+    pub fn from_mps(text: &str) -> Result<Document, ParseError> {
+        let mut section = Section::Name;
+        let mut sense = Sense::Minimize;
+        let mut objective_row = None;
+        let mut objective = LinearFunction::zero();
+        // Rows of the objective after the first one are ignored
+        let mut free_rows = vec![];
+        let mut rows: Vec<Row> = vec![];
+        let mut row_indices = HashMap::new();
+        let mut integer = false;
+        let mut integers = vec![];
+        let mut variables: Vec<Variable> = vec![];
+        let mut bounds: HashMap<Variable, Bounds> = HashMap::new();
+
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('*') {
+                continue;
+            }
+            // Sections start at the first column, and their fields on the following lines
+            if !line.starts_with([' ', '\t']) {
+                let mut words = line.split_whitespace();
+                let keyword = words.next().unwrap_or_default().to_uppercase();
+                section = match keyword.as_str() {
+                    "NAME" => Section::Name,
+                    "OBJSENSE" => Section::ObjSense,
+                    "ROWS" => Section::Rows,
+                    "COLUMNS" => Section::Columns,
+                    "RHS" => Section::Rhs,
+                    "RANGES" => Section::Ranges,
+                    "BOUNDS" => Section::Bounds,
+                    "ENDATA" => break,
+                    _ => {
+                        let expected = ["NAME", "ROWS", "COLUMNS", "RHS", "RANGES", "BOUNDS"];
+                        return Err(ParseError::unexpected(line, 0, &expected).on_line(i));
+                    }
+                };
+                // The sense can be given on the line of its section
+                if let (Section::ObjSense, Some(word)) = (section, words.next()) {
+                    let offset = word.as_ptr() as usize - line.as_ptr() as usize;
+                    sense = objective_sense(line, (offset, word)).map_err(|e| e.on_line(i))?;
+                }
+                continue;
+            }
+
+            // The free format comes first, unless only the fixed one reads numbers where values are
+            let fields = [free_fields(line), fixed_fields(line)]
+                .into_iter()
+                .filter(|fields| fits(section, fields))
+                .min_by_key(|fields| !has_numbers(section, fields));
+            let Some(fields) = fields else {
+                let whole = (line.len() - line.trim_start().len(), line.trim());
+                let message = format!("wrong number of fields in the {section:?} section");
+                return Err(error_on(line, whole, message).on_line(i));
+            };
+            let find_row = |field: Field| match row_indices.get(field.1) {
+                Some(&index) => Ok(Some(index)),
+                None if objective_row == Some(field.1) || free_rows.contains(&field.1) => Ok(None),
+                None => Err(error_on(line, field, format!("unknown row `{}`", field.1)).on_line(i)),
+            };
+
+            match section {
+                Section::Name => {}
+                Section::ObjSense => {
+                    sense = objective_sense(line, fields[0]).map_err(|e| e.on_line(i))?;
+                }
+                Section::Rows => {
+                    let (kind, name) = (fields[0], fields[1].1);
+                    let operator = match kind.1.to_uppercase().as_str() {
+                        "N" if objective_row.is_none() => {
+                            objective_row = Some(name);
+                            continue;
+                        }
+                        "N" => {
+                            free_rows.push(name);
+                            continue;
+                        }
+                        "L" => Operator::LessEqual,
+                        "G" => Operator::GreaterEqual,
+                        "E" => Operator::Equal,
+                        _ => {
+                            let expected = ["N", "L", "G", "E"];
+                            return Err(ParseError::unexpected(line, kind.0, &expected).on_line(i));
+                        }
+                    };
+                    row_indices.insert(name, rows.len());
+                    rows.push(Row {
+                        name: name.to_string(),
+                        operator,
+                        function: LinearFunction::zero(),
+                        rhs: 0.0,
+                        range: None,
+                    });
+                }
+                Section::Columns if fields[1].1 == "'MARKER'" => {
+                    integer = match fields[2].1 {
+                        "'INTORG'" => true,
+                        "'INTEND'" => false,
+                        _ => {
+                            let expected = ["'INTORG'", "'INTEND'"];
+                            return Err(
+                                ParseError::unexpected(line, fields[2].0, &expected).on_line(i)
+                            );
+                        }
+                    };
+                }
+                Section::Columns => {
+                    let var = fields[0].1.to_string();
+                    if !variables.contains(&var) {
+                        variables.push(var.clone());
+                        if integer {
+                            integers.push(var.clone());
+                        }
+                    }
+                    for entry in fields[1..].chunks(2) {
+                        let value = number(line, entry[1]).map_err(|e| e.on_line(i))?;
+                        let function = match find_row(entry[0])? {
+                            // Zeros only declare the variable
+                            _ if value == 0.0 => continue,
+                            Some(index) => &mut rows[index].function,
+                            None if objective_row == Some(entry[0].1) => &mut objective,
+                            None => continue,
+                        };
+                        function[&var] += value;
+                    }
+                }
+                Section::Rhs => {
+                    // The name of the set of values is optional
+                    for entry in fields[fields.len() % 2..].chunks(2) {
+                        let value = number(line, entry[1]).map_err(|e| e.on_line(i))?;
+                        match find_row(entry[0])? {
+                            Some(index) => rows[index].rhs = value,
+                            // The right-hand side of the objective is minus its constant
+                            None if objective_row == Some(entry[0].1) => {
+                                objective.constant = -value;
+                            }
+                            // Like their coefficients, values of the ignored rows are ignored
+                            None => {}
+                        }
+                    }
+                }
+                Section::Ranges => {
+                    for entry in fields[fields.len() % 2..].chunks(2) {
+                        let value = number(line, entry[1]).map_err(|e| e.on_line(i))?;
+                        match find_row(entry[0])? {
+                            Some(index) => rows[index].range = Some(value),
+                            None => {
+                                let message = "only constraints have ranges".to_string();
+                                return Err(error_on(line, entry[0], message).on_line(i));
+                            }
+                        }
+                    }
+                }
+                Section::Bounds => {
+                    let kind = fields[0].1.to_uppercase();
+                    let given = has_value(&kind) as usize;
+                    // The name of the set of bounds is optional
+                    let var = fields[fields.len() - 1 - given].1.to_string();
+                    let value = match given {
+                        1 => number(line, fields[fields.len() - 1]).map_err(|e| e.on_line(i))?,
+                        _ => 0.0,
+                    };
+                    let value = (value.abs() < INFINITY).then_some(value);
+                    let bound = bounds.entry(var.clone()).or_default();
+                    match kind.as_str() {
+                        "UP" | "UI" => bound.upper = value,
+                        "LO" | "LI" => bound.lower = value,
+                        "FX" => (bound.lower, bound.upper) = (value, value),
+                        "FR" => (bound.lower, bound.upper) = (None, None),
+                        "MI" => bound.lower = None,
+                        "PL" => bound.upper = None,
+                        "BV" => (bound.lower, bound.upper) = (Some(0.0), Some(1.0)),
+                        _ => {
+                            let expected = ["UP", "LO", "FX", "FR", "MI", "PL", "BV", "LI", "UI"];
+                            return Err(
+                                ParseError::unexpected(line, fields[0].0, &expected).on_line(i)
+                            );
+                        }
+                    }
+                    if matches!(kind.as_str(), "BV" | "LI" | "UI") && !integers.contains(&var) {
+                        integers.push(var.clone());
+                    }
+                    if !variables.contains(&var) {
+                        variables.push(var);
+                    }
+                }
+            }
+        }
+
+        let constant = |value: Coefficient| LinearFunction::new(value, HashMap::new());
+        let mut constraints = vec![];
+        for row in rows {
+            let name = Some(row.name);
+            let comparisons = match (row.range, row.operator) {
+                (None, operator) => vec![(operator, row.rhs)],
+                (Some(range), Operator::LessEqual) => vec![
+                    (Operator::GreaterEqual, row.rhs - range.abs()),
+                    (Operator::LessEqual, row.rhs),
+                ],
+                (Some(range), Operator::GreaterEqual) => vec![
+                    (Operator::GreaterEqual, row.rhs),
+                    (Operator::LessEqual, row.rhs + range.abs()),
+                ],
+                (Some(range), _) if range < 0.0 => vec![
+                    (Operator::GreaterEqual, row.rhs + range),
+                    (Operator::LessEqual, row.rhs),
+                ],
+                (Some(range), _) => vec![
+                    (Operator::GreaterEqual, row.rhs),
+                    (Operator::LessEqual, row.rhs + range),
+                ],
+            };
+            for (operator, rhs) in comparisons {
+                // A range is written as in `lo <= x + y <= hi`
+                let constraint = match operator {
+                    Operator::GreaterEqual if row.range.is_some() => {
+                        Constraint::new(constant(rhs), Operator::LessEqual, row.function.clone())
+                    }
+                    operator => Constraint::new(row.function.clone(), operator, constant(rhs)),
+                };
+                constraints.push(NamedConstraint {
+                    name: name.clone(),
+                    constraint,
+                });
+            }
+        }
+
+        let mut document = Document {
+            sense,
+            objective,
+            constraints,
+            integers,
+            ..Document::default()
+        };
+        for var in &variables {
+            if let Some(&bounds) = bounds.get(var) {
+                document.add_bounds(var, bounds);
+            }
+        }
+        // Names are changed once they are all read, so that those left as they are keep them
+        Ok(imported(&document, "C"))
+    }
+
+    /// Writes the document in the MPS format. Lines are laid out as in the fixed format, and
+    /// read in the free format when names are longer than 8 characters. A range written as
+    /// `c: lo <= x + y <= hi` gives a single ranged row. Names are made of ASCII letters,
+    /// digits and underscores, the parts `x⁺` and `x⁻` of a free variable being written
+    /// `x_pos` and `x_neg`, and the gap variable `ε1` being written `slack1`
+    pub fn to_mps(&self) -> String {
+        // A column named `MARKER` would be read as a marker
+        let document = exported(self, |name| name.eq_ignore_ascii_case("marker"));
+        let format = Format::default();
+        let (rows, objective_row) = rows(&document);
+
+        // Variables, in the order of their names
+        let mut variables: Vec<&Variable> = document.objective.var_iter().collect();
+        for row in &rows {
+            variables.extend(row.function.var_iter());
+        }
+        variables.extend(
+            document
+                .bounds
+                .iter()
+                .flat_map(|bound| bound.left.var_iter().chain(bound.right.var_iter())),
+        );
+        variables.extend(document.integers.iter().chain(&document.free));
+        variables.sort_by_key(|var| (natural_key(var), *var));
+        variables.dedup();
+
+        let mut lines = vec!["NAME".to_string(), "OBJSENSE".to_string()];
+        lines.push(match document.sense {
+            Sense::Maximize => "    MAX".to_string(),
+            Sense::Minimize => "    MIN".to_string(),
+        });
+        lines.push("ROWS".to_string());
+        lines.push(fixed_line(&["N", &objective_row]));
+        for row in &rows {
+            let kind = match row.operator {
+                Operator::Equal => "E",
+                Operator::LessEqual => "L",
+                _ => "G",
+            };
+            lines.push(fixed_line(&[kind, &row.name]));
+        }
+
+        lines.push("COLUMNS".to_string());
+        let mut integer = false;
+        for var in &variables {
+            if document.integers.contains(var) != integer {
+                integer = !integer;
+                let marker = if integer { "'INTORG'" } else { "'INTEND'" };
+                lines.push(fixed_line(&["", "MARKER", "'MARKER'", "", marker]));
+            }
+            let objective = (&objective_row, document.objective[var]);
+            let entries: Vec<(&String, Coefficient)> = std::iter::once(objective)
+                .chain(rows.iter().map(|row| (&row.name, row.function[var])))
+                .filter(|&(_, value)| value != 0.0)
+                .collect();
+            // A variable is written even if it has no coefficient
+            let entries = match entries.is_empty() {
+                true => vec![objective],
+                false => entries,
+            };
+            lines.extend(entry_lines(var, &entries));
+        }
+        if integer {
+            lines.push(fixed_line(&["", "MARKER", "'MARKER'", "", "'INTEND'"]));
+        }
+
+        let objective_rhs = (&objective_row, -document.objective.constant);
+        let rhs = std::iter::once(objective_rhs).chain(rows.iter().map(|row| (&row.name, row.rhs)));
+        let ranges = rows.iter().filter_map(|row| Some((&row.name, row.range?)));
+        for (section, set, entries) in [
+            ("RHS", "RHS", rhs.collect::<Vec<_>>()),
+            ("RANGES", "RNG", ranges.collect()),
+        ] {
+            let entries: Vec<_> = entries
+                .into_iter()
+                .filter(|&(_, value)| value != 0.0)
+                .collect();
+            if section == "RHS" || !entries.is_empty() {
+                lines.push(section.to_string());
+            }
+            lines.extend(entry_lines(set, &entries));
+        }
+
+        let bounds = document.variable_bounds();
+        let mut bound_lines = vec![];
+        for var in &variables {
+            let Bounds { lower, upper } = bounds.get(*var).copied().unwrap_or_default();
+            let mut bound = |kind: &str, value: Option<Coefficient>| {
+                let value = value.map(|value| format.number(value)).unwrap_or_default();
+                bound_lines.push(fixed_line(&[kind, "BND", var, &value]));
+            };
+            match (lower, upper) {
+                (Some(lower), Some(upper)) if lower == upper => {
+                    bound("FX", Some(lower));
+                    continue;
+                }
+                (None, None) => bound("FR", None),
+                (None, Some(_)) => bound("MI", None),
+                (Some(lower), _) if lower != 0.0 => bound("LO", Some(lower)),
+                _ => {}
+            }
+            if upper.is_some() {
+                bound("UP", upper);
+            }
+        }
+        if !bound_lines.is_empty() {
+            lines.push("BOUNDS".to_string());
+            lines.extend(bound_lines);
+        }
+        lines.push("ENDATA".to_string());
+        lines.into_iter().map(|line| line + "\n").collect()
+    }
+}
+
+/// Reads the sense of the objective
+fn objective_sense(line: &str, field: Field) -> Result<Sense, ParseError> {
+    let word = field.1.to_lowercase();
+    match OBJECTIVES.iter().find(|(keyword, _)| *keyword == word) {
+        Some(&(_, sense)) => Ok(sense),
+        None => Err(ParseError::unexpected(line, field.0, &["MAX", "MIN"])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LinearProgram;
+
+    /// Fixed format, with spaces in a name, integer markers, ranges and every kind of bound
+    const FIXED: &str = "\
+NAME          TESTLP
+* A comment
+ROWS
+ N  COST
+ L  LIM 1
+ G  LIM2
+ E  MYEQN
+COLUMNS
+    MARKER    'MARKER'                 'INTORG'
+    XONE      COST      1.0            LIM 1     1.0
+    XONE      LIM2      1.0
+    MARKER    'MARKER'                 'INTEND'
+    YTWO      COST      2.0            LIM 1     1.0
+    YTWO      MYEQN     -1.0
+    ZTHREE    COST      -1.0           LIM2      1.0
+    ZTHREE    MYEQN     1.0
+RHS
+    RHS       COST      -3.5           LIM2      1.0
+    RHS       LIM 1     4.0
+    RHS       MYEQN     7.0
+RANGES
+    RNG       LIM 1     2.5            MYEQN     -2.0
+BOUNDS
+ UP BND       XONE      4.0
+ LO BND       YTWO      -1.0
+ UP BND       YTWO      1.0
+ FX BND       ZTHREE    3.0
+ENDATA";
+
+    /// Free format, maximized, without names of sets
+    const FREE: &str = "\
+NAME example
+OBJSENSE MAXIMIZE
+ROWS
+ N obj
+ L capacity
+ N ignored
+COLUMNS
+ x obj 1 capacity 1
+ x ignored 5
+ y obj 6 capacity 1
+RHS
+ capacity 400 ignored 2
+BOUNDS
+ UP BND y 300
+ MI x
+ BV z
+ENDATA";
+
+    #[test]
+    fn test_fixed() {
+        let document = Document::from_mps(FIXED).unwrap();
+        assert_eq!(document.sense, Sense::Minimize);
+        assert_eq!(document.objective.constant, 3.5);
+        assert_eq!(document.integers, vec!["XONE"]);
+        assert_eq!(document.free, vec!["YTWO"]);
+
+        let constraints: Vec<String> = (document.constraints.iter())
+            .map(|c| format!("{}: {}", c.name.as_deref().unwrap(), c.constraint))
+            .collect();
+        assert_eq!(
+            constraints,
+            vec![
+                "LIM_1: 1.5 <= XONE + YTWO",
+                "LIM_1: XONE + YTWO <= 4",
+                "LIM2: XONE + ZTHREE >= 1",
+                "MYEQN: 5 <= -YTWO + ZTHREE",
+                "MYEQN: -YTWO + ZTHREE <= 7",
+            ]
+        );
+        let bounds: Vec<String> = document.bounds.iter().map(Constraint::to_string).collect();
+        assert_eq!(
+            bounds,
+            vec!["XONE <= 4", "YTWO >= -1", "YTWO <= 1", "ZTHREE = 3"]
+        );
+
+        // The ranges are written back as ranged rows
+        let written = document.to_mps();
+        assert!(written.contains("RANGES\n    RNG       LIM_1     2.5            MYEQN     2\n"));
+        assert_eq!(Document::from_mps(&written).unwrap(), document);
+        // The names are read back by documents
+        let (parsed, _) = Document::parse(&document.to_string()).unwrap();
+        assert_eq!(parsed, document);
+    }
+
+    #[test]
+    fn test_names() {
+        let text = "\
+ROWS
+ N obj
+ L max
+ L a-b
+ L a_b
+COLUMNS
+ free obj 1 max 1
+ 2x obj 1 a-b 1
+ x.y obj 1 a_b 1
+RHS
+ max 1 a-b 2
+ a_b 3
+ENDATA";
+        let document = Document::from_mps(text).unwrap();
+        assert_eq!(
+            document.to_string(),
+            "\
+min: C2x + free_ + x_y
+subject to
+  max_: free_ <= 1
+  a_b_v1: C2x <= 2
+  a_b: x_y <= 3
+"
+        );
+        let (parsed, _) = Document::parse(&document.to_string()).unwrap();
+        assert_eq!(parsed, document);
+    }
+
+    #[test]
+    fn test_free() {
+        let document = Document::from_mps(FREE).unwrap();
+        assert_eq!(document.sense, Sense::Maximize);
+        assert_eq!(
+            document.to_string(),
+            "\
+max: x + 6y
+subject to
+  capacity: x + y <= 400
+bounds
+  y <= 300
+  z <= 1
+int z
+free x
+"
+        );
+        let written = document.to_mps();
+        assert!(written.contains("\n FR BND       x\n"));
+        assert_eq!(Document::from_mps(&written).unwrap(), document);
+
+        // Programs are exported with their maximized objective, the parts of the free
+        // variable being given ASCII names
+        let program = LinearProgram::from_mps(FREE).unwrap();
+        let written = program.to_mps();
+        assert!(written.contains("\n    x_neg     OBJ       -1             R1        1\n"));
+        let exported = LinearProgram::from_mps(&written).unwrap();
+        let renamed = program.linear_function.renamed(|var| match var.as_str() {
+            "x⁺" => "x_pos".to_string(),
+            "x⁻" => "x_neg".to_string(),
+            var => var.to_string(),
+        });
+        assert_eq!(exported.linear_function, renamed);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |text: &str| Document::from_mps(text).unwrap_err();
+
+        let e = error("ROWS\n N obj\nCOLUMNS\n x cost 1");
+        assert_eq!((e.line, e.column, e.length), (3, 3, 4));
+        assert_eq!(e.message, "unknown row `cost`");
+
+        let e = error("ROWS\n N obj\nCOLUMNS\n x obj one");
+        assert_eq!((e.line, e.column), (3, 7));
+        assert_eq!(e.expected, vec!["a number"]);
+
+        let e = error("ROWS\n Q obj");
+        assert_eq!((e.line, e.column), (1, 1));
+
+        let e = error("ROWS\n N obj\nRANGES\n RNG obj 2");
+        assert_eq!(e.message, "only constraints have ranges");
+
+        let e = error("ROWS\n N obj extra fields here too");
+        assert_eq!((e.line, e.column), (1, 1));
+    }
+}
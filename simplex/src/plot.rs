@@ -0,0 +1,626 @@
+//! 2D representation of linear programs with two variables
+use crate::constraint::{Constraint, Operator};
+use crate::linear_function::{LinearFunction, Variable};
+use crate::LinearProgram;
+use egui::plot::{
+    CoordinatesFormatter, Corner, HLine, Legend, Line, LineStyle, MarkerShape, Plot, PlotPoint,
+    PlotPoints, Points, Polygon, Text, VLine,
+};
+use egui::{Align2, Color32, CursorIcon, Id, Pos2, Ui};
+use std::collections::HashMap;
+
+const FEASIBLE_COLOR: Color32 = Color32::from_rgb(69, 133, 136);
+const INFEASIBLE_COLOR: Color32 = Color32::from_rgb(204, 36, 29);
+const ISO_LINE_COLOR: Color32 = Color32::from_rgb(146, 131, 116);
+const PATH_COLOR: Color32 = Color32::from_rgb(242, 153, 51);
+const CURRENT_POINT_COLOR: Color32 = Color32::from_rgb(255, 64, 51);
+const OPTIMUM_COLOR: Color32 = Color32::from_rgb(255, 217, 25);
+const CANDIDATE_COLOR: Color32 = Color32::from_rgb(153, 217, 77);
+
+/// Distance in pixels under which a click picks a candidate vertex or grabs a constraint
+const PICK_DISTANCE: f32 = 12.0;
+
+/// Number of objective iso-lines drawn between the origin and the optimum
+const ISO_LINES: usize = 4;
+
+pub type Point2 = [f64; 2];
+
+/// Something the student did in the plot
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlotEvent {
+    /// A candidate vertex was clicked, to pivot towards it
+    CandidateClicked(usize),
+    /// A constraint was dragged to a new half-plane, sent on every frame it moves
+    ConstraintMoved(usize, HalfPlane),
+}
+
+/// A constraint being dragged, kept in the memory of egui between frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ConstraintDrag {
+    index: usize,
+    /// Point of the line the constraint rotates around, None when it is translated
+    pivot: Option<Point2>,
+    /// The constraint as it was grabbed, which every frame moves from so that no error adds up
+    grabbed: HalfPlane,
+    /// Where the constraint is dragged, drawn instead of it while the program catches up
+    moved: HalfPlane,
+}
+
+/// The half-plane a.(x, y) + c >= 0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HalfPlane {
+    pub a: Point2,
+    pub c: f64,
+}
+
+impl HalfPlane {
+    /// Builds the half-plane f(x, y) >= 0 from a linear function of the plotted variables
+    pub fn from_function(function: &LinearFunction, variables: &[Variable; 2]) -> HalfPlane {
+        HalfPlane {
+            a: [
+                function[&variables[0]] as f64,
+                function[&variables[1]] as f64,
+            ],
+            c: function.constant as f64,
+        }
+    }
+
+    pub fn value(&self, [x, y]: Point2) -> f64 {
+        self.a[0] * x + self.a[1] * y + self.c
+    }
+
+    /// The complementary half-plane
+    pub fn opposite(&self) -> HalfPlane {
+        HalfPlane {
+            a: [-self.a[0], -self.a[1]],
+            c: -self.c,
+        }
+    }
+
+    /// Returns the point of the line a.(x, y) + c = 0 closest to a point
+    pub fn project([x, y]: Point2, line: &HalfPlane) -> Point2 {
+        let t = line.value([x, y]) / (line.a[0] * line.a[0] + line.a[1] * line.a[1]);
+        [x - t * line.a[0], y - t * line.a[1]]
+    }
+
+    /// The half-plane translated so that its line goes through a point
+    pub fn through(&self, [x, y]: Point2) -> HalfPlane {
+        HalfPlane {
+            c: -(self.a[0] * x + self.a[1] * y),
+            ..*self
+        }
+    }
+
+    /// The half-plane rotated around a point of its line, so that its line goes through
+    /// another point. Its side and its largest coefficient are kept, so that a line turned
+    /// upright reads x <= c rather than 1.41x <= c
+    pub fn rotated(&self, pivot: Point2, towards: Point2) -> HalfPlane {
+        let direction = [towards[0] - pivot[0], towards[1] - pivot[1]];
+        let length = direction[0].abs().max(direction[1].abs());
+        if length == 0.0 {
+            return *self;
+        }
+        let largest = self.a[0].abs().max(self.a[1].abs());
+        let mut a = [
+            -direction[1] / length * largest,
+            direction[0] / length * largest,
+        ];
+        if a[0] * self.a[0] + a[1] * self.a[1] < 0.0 {
+            a = [-a[0], -a[1]];
+        }
+        HalfPlane { a, c: 0.0 }.through(pivot)
+    }
+
+    /// Writes the half-plane as a constraint on the plotted variables with the given operator,
+    /// its coefficients rounded to two decimals
+    pub fn as_constraint(&self, variables: &[Variable; 2], operator: Operator) -> Constraint {
+        let round = |value: f64| ((value * 100.0).round() / 100.0) as f32;
+        let function = |sign: f64| {
+            let coefficients = variables
+                .iter()
+                .zip(self.a)
+                .filter(|(var, a)| !var.is_empty() && round(*a) != 0.0)
+                .map(|(var, a)| (var.clone(), round(sign * a)))
+                .collect::<HashMap<_, _>>();
+            LinearFunction::new(0.0, coefficients)
+        };
+        let constant = |value: f64| LinearFunction::new(round(value), HashMap::new());
+        // The gap variable is right - left for <= and =, and left - right for >=
+        match operator {
+            Operator::GreaterEqual | Operator::Greater => {
+                Constraint::new(function(1.0), operator, constant(-self.c))
+            }
+            _ => Constraint::new(function(-1.0), operator, constant(self.c)),
+        }
+    }
+
+    /// Clips a convex polygon to the half-plane (Sutherland–Hodgman)
+    pub fn clip(&self, polygon: &[Point2]) -> Vec<Point2> {
+        let mut clipped = vec![];
+        for (i, &from) in polygon.iter().enumerate() {
+            let to = polygon[(i + 1) % polygon.len()];
+            let (value_from, value_to) = (self.value(from), self.value(to));
+            if value_from >= 0.0 {
+                clipped.push(from);
+            }
+            if value_from * value_to < 0.0 {
+                let t = value_from / (value_from - value_to);
+                clipped.push([
+                    from[0] + t * (to[0] - from[0]),
+                    from[1] + t * (to[1] - from[1]),
+                ]);
+            }
+        }
+        clipped
+    }
+
+    /// Returns the segment of the line a.(x, y) + c = 0 that lies inside a convex polygon
+    pub fn segment_in(&self, polygon: &[Point2]) -> Option<[Point2; 2]> {
+        let mut ends = vec![];
+        for (i, &from) in polygon.iter().enumerate() {
+            let to = polygon[(i + 1) % polygon.len()];
+            let (value_from, value_to) = (self.value(from), self.value(to));
+            if value_from == 0.0 {
+                ends.push(from);
+            } else if value_from * value_to < 0.0 {
+                let t = value_from / (value_from - value_to);
+                ends.push([
+                    from[0] + t * (to[0] - from[0]),
+                    from[1] + t * (to[1] - from[1]),
+                ]);
+            }
+        }
+        match ends[..] {
+            [a, b, ..] => Some([a, b]),
+            _ => None,
+        }
+    }
+}
+
+/// Distance between a point and a segment, on the screen
+fn distance_to_segment(point: Pos2, [from, to]: [Pos2; 2]) -> f32 {
+    let segment = to - from;
+    let t = ((point - from).dot(segment) / segment.length_sq()).clamp(0.0, 1.0);
+    point.distance(from + t * segment)
+}
+
+fn rectangle([min_x, min_y]: Point2, [max_x, max_y]: Point2) -> Vec<Point2> {
+    vec![
+        [min_x, min_y],
+        [max_x, min_y],
+        [max_x, max_y],
+        [min_x, max_y],
+    ]
+}
+
+/// Everything needed to draw a linear program with two variables
+pub struct Plot2D {
+    variables: [Variable; 2],
+    constraints: Vec<HalfPlane>,
+    objective: HalfPlane,
+    feasible_region: Vec<Point2>,
+    view_min: Point2,
+    view_max: Point2,
+    /// Paths taken by several pivot rules, each with its name and color
+    comparison_paths: Vec<(String, Color32, Vec<Vec<f32>>)>,
+    /// The constraints can be dragged, which is the case unless they come from a projection
+    pub draggable: bool,
+}
+
+impl Plot2D {
+    /// Builds the plot of a program in its initial state.
+    /// Programs with a single variable are drawn along the x axis
+    pub fn new(program: &LinearProgram) -> Plot2D {
+        let mut names = program.non_gap_variables().into_iter();
+        let variables = [
+            names.next().unwrap_or_default(),
+            names.next().unwrap_or_default(),
+        ];
+
+        // Each constraint is a gap variable, which has to stay positive, as do the variables
+        let mut constraints: Vec<HalfPlane> = program
+            .constraints
+            .iter()
+            .map(|c| HalfPlane::from_function(&c.right, &variables))
+            .collect();
+        constraints.push(HalfPlane {
+            a: [1.0, 0.0],
+            c: 0.0,
+        });
+        constraints.push(HalfPlane {
+            a: [0.0, 1.0],
+            c: 0.0,
+        });
+
+        // The feasible region is clipped to a box large enough to contain every bounded vertex
+        let extent = constraints
+            .iter()
+            .filter_map(|hp| {
+                let norm = hp.a[0].abs().max(hp.a[1].abs());
+                (norm > 0.0).then(|| hp.c.abs() / norm)
+            })
+            .fold(1.0f64, f64::max)
+            * 2.0;
+        let feasible_region = constraints
+            .iter()
+            .fold(rectangle([-extent; 2], [extent; 2]), |polygon, hp| {
+                hp.clip(&polygon)
+            });
+
+        // The view frames the feasible region with a margin
+        let (mut min, mut max) = ([0f64; 2], [0f64; 2]);
+        for point in &feasible_region {
+            for i in 0..2 {
+                min[i] = min[i].min(point[i]);
+                max[i] = max[i].max(point[i]);
+            }
+        }
+        for i in 0..2 {
+            let margin = ((max[i] - min[i]) * 0.2).max(1.0);
+            min[i] -= margin;
+            max[i] += margin;
+        }
+
+        Plot2D {
+            objective: HalfPlane::from_function(&program.linear_function, &variables),
+            variables,
+            constraints,
+            feasible_region,
+            view_min: min,
+            view_max: max,
+            comparison_paths: vec![],
+            draggable: true,
+        }
+    }
+
+    pub fn variables(&self) -> &[Variable; 2] {
+        &self.variables
+    }
+
+    /// Sets the paths taken by several pivot rules, drawn along with the path of the algorithm
+    pub fn set_comparison_paths(&mut self, paths: Vec<(String, Color32, Vec<Vec<f32>>)>) {
+        self.comparison_paths = paths;
+    }
+
+    /// Draws the plot along with the points visited by the algorithm up to the current step,
+    /// the marker of the current point, and the vertices the student can move to.
+    /// Constraints can be dragged to translate them, or to rotate them while holding shift.
+    /// Returns the candidate clicked or the constraint moved, if any
+    pub fn draw(
+        &self,
+        ui: &mut Ui,
+        points: &[Vec<f32>],
+        index: usize,
+        current_point: &[f32],
+        optimum: Option<&Vec<f32>>,
+        candidates: &[Vec<f32>],
+    ) -> Option<PlotEvent> {
+        let to_plot = |p: &[f32]| -> [f64; 2] {
+            [
+                p.first().copied().unwrap_or(0.0) as f64,
+                p.get(1).copied().unwrap_or(0.0) as f64,
+            ]
+        };
+        let [x_name, y_name] = self.variables.clone();
+        let (view_min, view_max) = (self.view_min, self.view_max);
+        let view = rectangle(view_min, view_max);
+
+        // Grabbing a constraint replaces panning the plot
+        let (drag_id, hover_id) = (Id::new("constraint drag"), Id::new("constraint hover"));
+        let drag = ui.data_mut(|d| d.get_temp::<ConstraintDrag>(drag_id));
+        let hovered = ui.data_mut(|d| d.get_temp::<usize>(hover_id));
+
+        Plot::new("2D view")
+            .allow_drag(drag.is_none() && hovered.is_none())
+            .legend(Legend::default())
+            .coordinates_formatter(
+                Corner::LeftBottom,
+                CoordinatesFormatter::new(move |point, _| {
+                    format!("{x_name} = {:.2}\n{y_name} = {:.2}", point.x, point.y)
+                }),
+            )
+            .include_x(view_min[0])
+            .include_x(view_max[0])
+            .include_y(view_min[1])
+            .include_y(view_max[1])
+            .show(ui, |plot_ui| {
+                let mut event = None;
+
+                // Constraints of the program come first, then the axes
+                let pointer = plot_ui.ctx().input(|i| i.pointer.hover_pos());
+                let (pressed, down, shift) = plot_ui.ctx().input(|i| {
+                    (
+                        i.pointer.primary_pressed(),
+                        i.pointer.primary_down(),
+                        i.modifiers.shift,
+                    )
+                });
+                let program_constraints = self.constraints.len() - 2;
+                let hovered = match pointer {
+                    Some(pointer) if self.draggable && plot_ui.plot_hovered() => self.constraints
+                        [..program_constraints]
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(i, constraint)| {
+                            let [from, to] = constraint.segment_in(&view)?;
+                            let segment = [from, to]
+                                .map(|[x, y]| plot_ui.screen_from_plot(PlotPoint::new(x, y)));
+                            Some((i, distance_to_segment(pointer, segment)))
+                        })
+                        .filter(|(_, distance)| *distance <= PICK_DISTANCE)
+                        .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+                        .map(|(i, _)| i),
+                    _ => None,
+                };
+                // The program is solved again on every frame the constraint moves
+                let previous = drag.map(|drag| drag.moved);
+                let drag = match (drag, hovered, pointer) {
+                    (Some(drag), _, Some(_)) if down => Some(drag),
+                    (None, Some(index), Some(pointer)) if pressed && down => {
+                        let pointer = plot_ui.plot_from_screen(pointer);
+                        let grabbed = self.constraints[index];
+                        let pivot = HalfPlane::project([pointer.x, pointer.y], &grabbed);
+                        let drag = ConstraintDrag {
+                            index,
+                            pivot: shift.then_some(pivot),
+                            grabbed,
+                            moved: grabbed,
+                        };
+                        Some(drag)
+                    }
+                    _ => None,
+                };
+                let drag = match (drag, pointer) {
+                    (Some(mut drag), Some(pointer)) => {
+                        let pointer = plot_ui.plot_from_screen(pointer);
+                        drag.moved = match drag.pivot {
+                            Some(pivot) if !self.variables[1].is_empty() => {
+                                drag.grabbed.rotated(pivot, [pointer.x, pointer.y])
+                            }
+                            _ => drag.grabbed.through([pointer.x, pointer.y]),
+                        };
+                        Some(drag)
+                    }
+                    (drag, _) => drag,
+                };
+                if let Some(drag) =
+                    drag.filter(|drag| drag.moved != previous.unwrap_or(drag.grabbed))
+                {
+                    event = Some(PlotEvent::ConstraintMoved(drag.index, drag.moved));
+                }
+                if drag.is_some() || hovered.is_some() {
+                    plot_ui.ctx().set_cursor_icon(CursorIcon::Grab);
+                }
+                plot_ui.ctx().data_mut(|d| {
+                    match drag {
+                        Some(drag) => d.insert_temp(drag_id, drag),
+                        None => d.remove::<ConstraintDrag>(drag_id),
+                    }
+                    match hovered {
+                        Some(hovered) => d.insert_temp(hover_id, hovered),
+                        None => d.remove::<usize>(hover_id),
+                    }
+                });
+                let grabbed = drag.map(|drag| drag.index).or(hovered);
+
+                // Axes, named after the variables
+                plot_ui.hline(HLine::new(0.0).color(Color32::GRAY));
+                plot_ui.vline(VLine::new(0.0).color(Color32::GRAY));
+                plot_ui.text(
+                    Text::new(PlotPoint::new(view_max[0], 0.0), &self.variables[0])
+                        .anchor(Align2::RIGHT_BOTTOM),
+                );
+                plot_ui.text(
+                    Text::new(PlotPoint::new(0.0, view_max[1]), &self.variables[1])
+                        .anchor(Align2::LEFT_TOP),
+                );
+
+                // Shade the infeasible side of every constraint, the grabbed one being thicker
+                for (i, constraint) in self.constraints.iter().enumerate() {
+                    let constraint = match drag {
+                        Some(drag) if drag.index == i => drag.moved,
+                        _ => *constraint,
+                    };
+                    let infeasible = constraint.opposite().clip(&view);
+                    if infeasible.len() >= 3 {
+                        plot_ui.polygon(
+                            Polygon::new(PlotPoints::from(infeasible))
+                                .color(INFEASIBLE_COLOR)
+                                .fill_alpha(0.08)
+                                .width(0.0),
+                        );
+                    }
+                    if let Some(segment) = constraint.segment_in(&view) {
+                        plot_ui.line(
+                            Line::new(PlotPoints::from(segment.to_vec()))
+                                .color(INFEASIBLE_COLOR)
+                                .width(if grabbed == Some(i) { 3.0 } else { 1.0 })
+                                .name("Constraints"),
+                        );
+                    }
+                }
+
+                if self.feasible_region.len() >= 3 {
+                    plot_ui.polygon(
+                        Polygon::new(PlotPoints::from(self.feasible_region.clone()))
+                            .color(FEASIBLE_COLOR)
+                            .fill_alpha(0.4)
+                            .name("Feasible region"),
+                    );
+                }
+
+                // Iso-lines of the objective, up to its optimal value
+                let current_value = self.objective.value(to_plot(current_point));
+                let best_value = optimum
+                    .map(|p| self.objective.value(to_plot(p)))
+                    .unwrap_or(current_value);
+                for i in 0..=ISO_LINES {
+                    let value = best_value * i as f64 / ISO_LINES as f64;
+                    let iso_line = HalfPlane {
+                        c: self.objective.c - value,
+                        ..self.objective
+                    };
+                    if let Some(segment) = iso_line.segment_in(&view) {
+                        plot_ui.line(
+                            Line::new(PlotPoints::from(segment.to_vec()))
+                                .color(ISO_LINE_COLOR)
+                                .style(LineStyle::dashed_loose())
+                                .name("Objective"),
+                        );
+                    }
+                }
+                let current_iso_line = HalfPlane {
+                    c: self.objective.c - current_value,
+                    ..self.objective
+                };
+                if let Some(segment) = current_iso_line.segment_in(&view) {
+                    plot_ui.line(
+                        Line::new(PlotPoints::from(segment.to_vec()))
+                            .color(CURRENT_POINT_COLOR)
+                            .style(LineStyle::dashed_dense())
+                            .name("Objective"),
+                    );
+                }
+
+                for (name, color, points) in &self.comparison_paths {
+                    let path: Vec<[f64; 2]> = points.iter().map(|p| to_plot(p)).collect();
+                    plot_ui.line(
+                        Line::new(PlotPoints::from(path))
+                            .color(*color)
+                            .width(2.0)
+                            .style(LineStyle::dashed_dense())
+                            .name(name),
+                    );
+                }
+
+                // Path of the algorithm, vertex by vertex
+                let path: Vec<[f64; 2]> =
+                    points.iter().take(index + 1).map(|p| to_plot(p)).collect();
+                plot_ui.line(
+                    Line::new(PlotPoints::from(path.clone()))
+                        .color(PATH_COLOR)
+                        .width(2.0)
+                        .name("Path"),
+                );
+                plot_ui.points(
+                    Points::new(PlotPoints::from(path))
+                        .color(PATH_COLOR)
+                        .radius(4.0)
+                        .name("Path"),
+                );
+                if let Some(optimum) = optimum {
+                    plot_ui.points(
+                        Points::new(PlotPoints::from(vec![to_plot(optimum)]))
+                            .shape(MarkerShape::Asterisk)
+                            .color(OPTIMUM_COLOR)
+                            .radius(10.0)
+                            .name("Optimum"),
+                    );
+                }
+                plot_ui.points(
+                    Points::new(PlotPoints::from(vec![to_plot(current_point)]))
+                        .color(CURRENT_POINT_COLOR)
+                        .radius(6.0)
+                        .name("Current point"),
+                );
+
+                if !candidates.is_empty() {
+                    let candidates: Vec<[f64; 2]> = candidates.iter().map(|p| to_plot(p)).collect();
+                    plot_ui.points(
+                        Points::new(PlotPoints::from(candidates.clone()))
+                            .color(CANDIDATE_COLOR)
+                            .radius(6.0)
+                            .name("Possible moves"),
+                    );
+                    if let (true, Some(pointer)) =
+                        (plot_ui.plot_clicked(), plot_ui.pointer_coordinate())
+                    {
+                        let pointer = plot_ui.screen_from_plot(pointer);
+                        event = candidates
+                            .iter()
+                            .map(|[x, y]| {
+                                plot_ui
+                                    .screen_from_plot(PlotPoint::new(*x, *y))
+                                    .distance(pointer)
+                            })
+                            .enumerate()
+                            .filter(|(_, distance)| *distance <= PICK_DISTANCE)
+                            .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+                            .map(|(i, _)| PlotEvent::CandidateClicked(i))
+                            .or(event);
+                    }
+                }
+                event
+            })
+            .inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clip() {
+        let square = rectangle([0.0, 0.0], [2.0, 2.0]);
+        // x + y <= 2
+        let half_plane = HalfPlane {
+            a: [-1.0, -1.0],
+            c: 2.0,
+        };
+
+        assert_eq!(
+            half_plane.clip(&square),
+            vec![[0.0, 0.0], [2.0, 0.0], [0.0, 2.0]]
+        );
+    }
+
+    #[test]
+    fn test_move_constraint() {
+        let variables = ["x".to_string(), "y".to_string()];
+        // x + y <= 2
+        let half_plane = HalfPlane {
+            a: [-1.0, -1.0],
+            c: 2.0,
+        };
+
+        let translated = half_plane.through([2.0, 1.0]);
+        assert_eq!(translated.c, 3.0);
+        assert_eq!(
+            translated.as_constraint(&variables, Operator::LessEqual),
+            "x + y <= 3".parse().unwrap()
+        );
+        assert_eq!(
+            translated.as_constraint(&variables, Operator::GreaterEqual),
+            "-x - y >= -3".parse().unwrap()
+        );
+
+        // Rotating around (2, 0) until the line goes through (2, 1) gives x <= 2
+        let rotated = half_plane.rotated([2.0, 0.0], [2.0, 1.0]);
+        assert_eq!(
+            rotated.as_constraint(&variables, Operator::LessEqual),
+            "x <= 2".parse().unwrap()
+        );
+        // Its largest coefficient stays 1, whatever the angle
+        let rotated = half_plane.rotated([2.0, 0.0], [3.0, 2.0]);
+        assert_eq!(
+            rotated.as_constraint(&variables, Operator::LessEqual),
+            "x - 0.5y <= 2".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_segment_in() {
+        let square = rectangle([0.0, 0.0], [2.0, 2.0]);
+        // x = 1
+        let half_plane = HalfPlane {
+            a: [1.0, 0.0],
+            c: -1.0,
+        };
+
+        assert_eq!(
+            half_plane.segment_in(&square),
+            Some([[1.0, 0.0], [1.0, 2.0]])
+        );
+    }
+}
@@ -1,22 +1,200 @@
 //! Used to create a kD representation of a set of constraints to be rendered
-use std::f32::consts::FRAC_PI_4;
-use std::mem::size_of_val;
+use std::mem::{size_of, size_of_val};
 use std::slice::from_raw_parts;
 
-use crate::Constraints;
+use crate::camera::Camera;
+use crate::hull::{plane_section, Point, Polyhedron};
+use crate::linear_function::{LinearFunction, Variable};
+use crate::LinearProgram;
 use eframe::glow::HasContext;
 use eframe::{egui_glow, glow};
-use egui::Vec2;
-use glm::{Matrix4, Vector3};
-use num_traits::identities::One;
+use egui::{Color32, Pos2, Rect, Rgba};
+use glm::{Matrix4, Vector4};
+use itertools::Itertools;
+
+/// Layout of every vertex sent to the GPU: a position followed by a normal
+type Vertex = [f32; 6];
+
+const FACE_COLOR: Rgba = Rgba::from_rgb(0.27, 0.52, 0.53);
+const EDGE_COLOR: Rgba = Rgba::from_rgb(0.85, 0.9, 0.9);
+const VERTEX_COLOR: Rgba = Rgba::from_rgb(0.85, 0.9, 0.9);
+const CURRENT_POINT_COLOR: Rgba = Rgba::from_rgb(1.0, 0.25, 0.2);
+const PATH_COLOR: Rgba = Rgba::from_rgb(0.95, 0.6, 0.2);
+const CURRENT_STEP_COLOR: Rgba = Rgba::from_rgb(1.0, 0.25, 0.2);
+const CANDIDATE_COLOR: Rgba = Rgba::from_rgb(0.6, 0.85, 0.3);
+const OPTIMUM_COLOR: Rgba = Rgba::from_rgb(1.0, 0.85, 0.1);
+const HIGHLIGHT_COLOR: Rgba = Rgba::from_rgb(1.0, 0.85, 0.1);
+const OBJECTIVE_COLOR: Rgba = Rgba::from_rgb(0.95, 0.95, 0.95);
+/// Colors given to the constraint planes, in turn
+const PLANE_COLORS: [Rgba; 6] = [
+    Rgba::from_rgb(0.8, 0.14, 0.11),
+    Rgba::from_rgb(0.6, 0.59, 0.1),
+    Rgba::from_rgb(0.84, 0.6, 0.13),
+    Rgba::from_rgb(0.27, 0.52, 0.53),
+    Rgba::from_rgb(0.69, 0.38, 0.53),
+    Rgba::from_rgb(0.41, 0.62, 0.42),
+];
+const AXIS_COLOR: Rgba = Rgba::from_rgb(0.75, 0.75, 0.75);
+const GRID_COLOR: Rgba = Rgba::from_rgb(0.3, 0.3, 0.3);
+/// Length of the tick marks, in the normalized coordinates of the polyhedron
+const TICK_LENGTH: f32 = 0.03;
+/// Distance in pixels under which the pointer shows the coordinates of a vertex
+const VERTEX_HOVER_DISTANCE: f32 = 10.0;
+/// Distance between the paths of different pivot rules, so that shared edges stay visible
+const COMPARISON_OFFSET: f32 = 0.015;
+/// Distance to the ray under the pointer under which a point is picked, without zoom
+const PICK_DISTANCE: f32 = 0.05;
+/// Opacity of the constraint and objective planes
+const PLANE_ALPHA: f32 = 0.25;
+/// How far the planes extend beyond the polyhedron, in its normalized coordinates
+const PLANE_MARGIN: f32 = 0.2;
+
+/// A vertex buffer along with the number of vertices it holds
+struct Mesh {
+    buffer: glow::Buffer,
+    len: i32,
+}
+
+impl Mesh {
+    unsafe fn new(gl: &glow::Context) -> Mesh {
+        Mesh {
+            buffer: gl.create_buffer().expect("failed to create buffer"),
+            len: 0,
+        }
+    }
+
+    unsafe fn upload(&mut self, gl: &glow::Context, vertices: &[Vertex], usage: u32) {
+        let data: &[u8] = from_raw_parts(vertices.as_ptr().cast(), size_of_val(vertices));
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.buffer));
+        gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, data, usage);
+        self.len = vertices.len() as i32;
+    }
+
+    unsafe fn draw(&self, gl: &glow::Context, mode: u32) {
+        self.draw_range(gl, mode, 0, self.len);
+    }
+
+    unsafe fn draw_range(&self, gl: &glow::Context, mode: u32, first: i32, count: i32) {
+        let stride = size_of::<Vertex>() as i32;
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.buffer));
+        gl.vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, stride, 0);
+        gl.vertex_attrib_pointer_f32(
+            1,
+            3,
+            glow::FLOAT,
+            false,
+            stride,
+            3 * size_of::<f32>() as i32,
+        );
+        gl.draw_arrays(mode, first, count);
+    }
+}
+
+/// The plane a.x + c = 0 of a constraint, in the coordinates of the polyhedron
+struct ConstraintPlane {
+    a: Point,
+    c: f32,
+    label: String,
+    /// Polygon where the plane cuts the box around the polyhedron
+    section: Vec<Point>,
+}
+
+/// An axis of the program, graduated in its original units
+struct Axis {
+    name: Variable,
+    ticks: Vec<f32>,
+    /// Value at the tip of the axis
+    end: f32,
+}
+
+/// Returns round values from 0 to 'max', spaced by 1, 2 or 5 times a power of ten
+fn ticks(max: f32) -> Vec<f32> {
+    if max <= 0.0 {
+        return vec![0.0];
+    }
+    // Aim for about five ticks
+    let rough_step = max / 5.0;
+    let magnitude = 10f32.powf(rough_step.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|step| *step >= rough_step)
+        .unwrap_or(10.0 * magnitude);
+    (0..)
+        .map(|i| i as f32 * step)
+        .take_while(|t| *t <= max * (1.0 + 1e-6))
+        .collect()
+}
+
+/// Writes a value without trailing zeros
+fn format_value(value: f32) -> String {
+    let text = format!("{value:.2}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Triangulates a convex polygon as a fan, with the same normal for every vertex
+fn fan(polygon: &[Point], [nx, ny, nz]: Point) -> Vec<Vertex> {
+    (1..polygon.len().saturating_sub(1))
+        .flat_map(|i| [polygon[0], polygon[i], polygon[i + 1]])
+        .map(|[x, y, z]| [x, y, z, nx, ny, nz])
+        .collect()
+}
+
+/// Center of a polygon, where its label is drawn
+fn centroid(polygon: &[Point]) -> Point {
+    let count = polygon.len().max(1) as f32;
+    [0, 1, 2].map(|i| polygon.iter().map(|p| p[i]).sum::<f32>() / count)
+}
 
 pub struct PolyhedronRenderer {
     rendering_program: glow::Program,
     vertex_array: glow::VertexArray,
-    buffer: glow::Buffer,
+    faces: Mesh,
+    edges: Mesh,
+    vertices: Mesh,
+    current_point: Mesh,
+    path: Mesh,
+    current_step: Mesh,
+    optimum: Mesh,
+    candidates: Mesh,
+    comparison: Mesh,
+    planes: Mesh,
+    objective_plane: Mesh,
+    highlight: Mesh,
+    axes: Mesh,
+    grid: Mesh,
 
-    vertices: Vec<[f32; 3]>,
-    pub view_angle: Vec2,
+    polyhedron: Polyhedron,
+    axes_values: Vec<Axis>,
+    constraint_planes: Vec<ConstraintPlane>,
+    /// The objective a.x + c, in the coordinates of the polyhedron
+    objective: (Point, f32),
+    /// Smallest and largest values of the objective on the polyhedron
+    objective_range: (f32, f32),
+    hovered: Option<usize>,
+    path_points: Vec<Vec<f32>>,
+    path_index: usize,
+    optimum_point: Option<Vec<f32>>,
+    /// Vertices the student can move to in manual mode
+    candidate_points: Vec<Vec<f32>>,
+    /// Paths taken by several pivot rules, each with its color
+    comparison_paths: Vec<(Color32, Vec<Vec<f32>>)>,
+    needs_upload: bool,
+    pub camera: Camera,
+    /// Opacity of the faces, lower values let the inside of the polyhedron show through
+    pub alpha: f32,
+    /// Draws every constraint as a plane
+    pub show_constraint_planes: bool,
+    /// Draws the iso-plane of the objective at `objective_level`
+    pub show_objective_plane: bool,
+    pub objective_level: f32,
+    /// Draws the axes and a grid on the ground
+    pub show_axes: bool,
 }
 
 impl PolyhedronRenderer {
@@ -34,19 +212,35 @@ impl PolyhedronRenderer {
             let (vertex_shader_src, fragment_shader_src) = (
                 r#"
                     uniform mat4 u_mvp;
+                    uniform mat4 u_model;
+                    uniform float u_point_size;
                     in vec3 vert;
+                    in vec3 normal;
+                    out vec3 v_normal;
 
                     void main() {
                         gl_Position = u_mvp * vec4(vert, 1.0);
+                        gl_PointSize = u_point_size;
+                        v_normal = (u_model * vec4(normal, 0.0)).xyz;
                     }
                 "#,
                 r#"
                     precision mediump float;
+                    uniform vec4 u_color;
+                    uniform bool u_lit;
+                    in vec3 v_normal;
                     out vec4 out_color;
 
                     void main() {
-                        vec3 ambient_color = 1.0 * vec3(0.27, 0.52, 0.53);
-                        out_color = vec4(ambient_color, 1.0);
+                        vec3 color = u_color.rgb;
+                        if (u_lit) {
+                            vec3 light_direction = normalize(vec3(0.4, 0.8, 1.0));
+                            float ambient = 0.35;
+                            float diffuse = max(dot(normalize(v_normal), light_direction), 0.0);
+                            color *= ambient + (1.0 - ambient) * diffuse;
+                        }
+                        // egui blends with premultiplied alpha
+                        out_color = vec4(color * u_color.a, u_color.a);
                     }
                 "#,
             );
@@ -72,6 +266,8 @@ impl PolyhedronRenderer {
                 })
                 .collect();
 
+            gl.bind_attrib_location(rendering_program, 0, "vert");
+            gl.bind_attrib_location(rendering_program, 1, "normal");
             gl.link_program(rendering_program);
             if !gl.get_program_link_status(rendering_program) {
                 return Err("failed to link".to_string());
@@ -87,73 +283,541 @@ impl PolyhedronRenderer {
                 vertex_array: gl
                     .create_vertex_array()
                     .expect("failed to create vertex array"),
-                buffer: gl.create_buffer().expect("failed to create buffer"),
-                vertices: vec![],
-                view_angle: Vec2::default(),
+                faces: Mesh::new(gl),
+                edges: Mesh::new(gl),
+                vertices: Mesh::new(gl),
+                current_point: Mesh::new(gl),
+                path: Mesh::new(gl),
+                current_step: Mesh::new(gl),
+                optimum: Mesh::new(gl),
+                candidates: Mesh::new(gl),
+                comparison: Mesh::new(gl),
+                planes: Mesh::new(gl),
+                objective_plane: Mesh::new(gl),
+                highlight: Mesh::new(gl),
+                axes: Mesh::new(gl),
+                grid: Mesh::new(gl),
+                polyhedron: Polyhedron::default(),
+                axes_values: vec![],
+                constraint_planes: vec![],
+                objective: ([0.0; 3], 0.0),
+                objective_range: (0.0, 0.0),
+                hovered: None,
+                path_points: vec![],
+                path_index: 0,
+                optimum_point: None,
+                candidate_points: vec![],
+                comparison_paths: vec![],
+                needs_upload: false,
+                camera: Camera::default(),
+                alpha: 0.6,
+                show_constraint_planes: false,
+                show_objective_plane: false,
+                objective_level: 0.0,
+                show_axes: true,
             }
         })
     }
 
-    pub fn polyhedron_from_constraints(&mut self, constraints: &Constraints) {
-        self.vertices = constraints.polyhedron();
+    /// Builds the polyhedron of a program with three variables or less, along with the planes
+    /// of its constraints, labelled in order, and of its objective
+    pub fn set_program(&mut self, program: &LinearProgram, labels: Vec<String>) {
+        self.polyhedron = program.constraints.polyhedron();
+        // Coordinates of the polyhedron follow the alphabetical order of the variables
+        let variables = program.constraints.non_gap_variables();
+        let variables: Vec<_> = variables.iter().sorted().collect();
+        let plane = |function: &LinearFunction| {
+            let mut a = [0f32; 3];
+            for (dest, var) in a.iter_mut().zip(&variables) {
+                *dest = function[var];
+            }
+            self.polyhedron.normalize_plane(a, function.constant)
+        };
+
+        let (min, max) = self.polyhedron.bounds();
+        let (min, max) = (min.map(|v| v - PLANE_MARGIN), max.map(|v| v + PLANE_MARGIN));
+        self.constraint_planes = program
+            .constraints
+            .iter()
+            .zip(labels)
+            .map(|(constraint, label)| {
+                let (a, c) = plane(&constraint.right);
+                ConstraintPlane {
+                    a,
+                    c,
+                    label,
+                    section: plane_section(a, c, min, max),
+                }
+            })
+            .collect();
+
+        // Axes start at the origin and go slightly beyond the polyhedron
+        let (_, max) = self.polyhedron.bounds();
+        let max = self.polyhedron.denormalize(max);
+        self.axes_values = variables
+            .iter()
+            .zip(max)
+            .map(|(name, max)| Axis {
+                name: name.to_string(),
+                ticks: ticks(max),
+                end: max.max(0.0) * 1.15 + f32::EPSILON,
+            })
+            .collect();
+
+        self.objective = plane(&program.linear_function);
+        let (a, c) = self.objective;
+        let values = self
+            .polyhedron
+            .vertices
+            .iter()
+            .map(|v| a.iter().zip(v).map(|(a, x)| a * x).sum::<f32>() + c);
+        self.objective_range = values.fold((f32::MAX, f32::MIN), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+        if self.polyhedron.vertices.is_empty() {
+            self.objective_range = (0.0, 0.0);
+        }
+        self.objective_level = self.objective_range.0;
+        self.hovered = None;
+        self.needs_upload = true;
+    }
+
+    /// Smallest and largest values of the objective on the polyhedron
+    pub fn objective_range(&self) -> (f32, f32) {
+        self.objective_range
+    }
+
+    /// Highlights the facet lying on a constraint, given by its index
+    pub fn set_hovered(&mut self, constraint: Option<usize>) {
+        self.hovered = constraint;
+    }
+
+    /// Returns the index of the constraint whose facet is under the pointer, if any
+    pub fn hovered_constraint(&self, rect: Rect, pointer: Pos2) -> Option<usize> {
+        let (origin, direction) = self.ray(rect, pointer);
+        let face = self.polyhedron.ray_cast(origin, direction)?;
+        self.constraint_planes.iter().position(|plane| {
+            self.polyhedron
+                .faces_on_plane(plane.a, plane.c)
+                .contains(&face)
+        })
+    }
+
+    /// Sets the vertices the student can move to, given in the program's coordinates
+    pub fn set_candidates(&mut self, points: Vec<Vec<f32>>) {
+        self.candidate_points = points;
+    }
+
+    /// Sets the paths taken by several pivot rules, given in the program's coordinates
+    pub fn set_comparison_paths(&mut self, paths: Vec<(Color32, Vec<Vec<f32>>)>) {
+        self.comparison_paths = paths;
+    }
+
+    /// Returns the index of the candidate vertex closest to the ray under the pointer, if any
+    pub fn candidate_at(&self, rect: Rect, pointer: Pos2) -> Option<usize> {
+        let (origin, direction) = self.ray(rect, pointer);
+        let length = direction.iter().map(|d| d * d).sum::<f32>().sqrt();
+        let direction = direction.map(|d| d / length);
+        let distance_to_ray = |point: &Point| {
+            let to_point = [0, 1, 2].map(|i| point[i] - origin[i]);
+            let along = (0..3).map(|i| to_point[i] * direction[i]).sum::<f32>();
+            (0..3)
+                .map(|i| (to_point[i] - along * direction[i]).powi(2))
+                .sum::<f32>()
+                .sqrt()
+        };
+        self.candidate_points
+            .iter()
+            .map(|p| distance_to_ray(&self.polyhedron.normalize(p)))
+            .enumerate()
+            .filter(|(_, distance)| *distance <= PICK_DISTANCE / self.camera.zoom)
+            .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+            .map(|(i, _)| i)
+    }
+
+    /// Returns the origin and the direction of the ray going from the camera through the
+    /// pointer, in the normalized coordinates of the polyhedron
+    fn ray(&self, rect: Rect, pointer: Pos2) -> (Point, Point) {
+        // Unproject the pointer on the near plane and on the far plane
+        let inverse = glm::inverse(&self.camera.transform(rect.aspect_ratio()).0);
+        let x = 2.0 * (pointer.x - rect.left()) / rect.width() - 1.0;
+        let y = 1.0 - 2.0 * (pointer.y - rect.top()) / rect.height();
+        let unproject = |z: f32| {
+            let p = inverse * Vector4::new(x, y, z, 1.0);
+            [p.x / p.w, p.y / p.w, p.z / p.w]
+        };
+        let (near, far) = (unproject(-1.0), unproject(1.0));
+        (near, [0, 1, 2].map(|i| far[i] - near[i]))
+    }
+
+    /// Position on the screen of a point of the polyhedron, if it is in front of the camera
+    fn to_screen(&self, rect: Rect, [x, y, z]: Point) -> Option<Pos2> {
+        let p = self.camera.transform(rect.aspect_ratio()).0 * Vector4::new(x, y, z, 1.0);
+        (p.w > 0.0).then(|| {
+            Pos2::new(
+                rect.left() + (p.x / p.w + 1.0) / 2.0 * rect.width(),
+                rect.top() + (1.0 - p.y / p.w) / 2.0 * rect.height(),
+            )
+        })
+    }
+
+    /// A point on an axis, in the normalized coordinates of the polyhedron
+    fn on_axis(&self, axis: usize, value: f32) -> Point {
+        let mut point = [0f32; 3];
+        point[axis] = value;
+        self.polyhedron.normalize(&point)
+    }
+
+    /// Returns the axes with their tick marks, and the grid on the ground,
+    /// which is the plane of the first and third variables
+    fn axes_and_grid(&self) -> (Vec<Vertex>, Vec<Vertex>) {
+        let as_vertex = |[x, y, z]: Point| [x, y, z, 0.0, 0.0, 0.0];
+        let mut axes = vec![];
+        for (i, axis) in self.axes_values.iter().enumerate() {
+            axes.push(as_vertex(self.on_axis(i, 0.0)));
+            axes.push(as_vertex(self.on_axis(i, axis.end)));
+            // Tick marks are drawn across the axis, along the next one
+            let mut across = [0f32; 3];
+            across[(i + 1) % 3] = TICK_LENGTH;
+            for tick in &axis.ticks {
+                let p = self.on_axis(i, *tick);
+                axes.push(as_vertex([0, 1, 2].map(|j| p[j] - across[j])));
+                axes.push(as_vertex([0, 1, 2].map(|j| p[j] + across[j])));
+            }
+        }
+
+        let mut grid = vec![];
+        if let [x, _, z] = &self.axes_values[..] {
+            for (along, other, axis) in [(0, 2, x), (2, 0, z)] {
+                let end = self.axes_values[other].end;
+                for tick in &axis.ticks {
+                    let mut from = [0f32; 3];
+                    from[along] = *tick;
+                    let mut to = from;
+                    to[other] = end;
+                    grid.push(as_vertex(self.polyhedron.normalize(&from)));
+                    grid.push(as_vertex(self.polyhedron.normalize(&to)));
+                }
+            }
+        }
+        (axes, grid)
     }
 
-    pub fn draw(&mut self, gl: &glow::Context, rect_size: [u32; 2], current_point: &[f32; 3]) {
+    /// Returns the labels of the scene with their position on the screen: the axes and their
+    /// ticks, the planes drawn, and the coordinates of the vertex under the pointer
+    pub fn labels(&self, rect: Rect, pointer: Option<Pos2>) -> Vec<(Pos2, String, Color32)> {
+        let to_screen = |point: Point| self.to_screen(rect, point);
+
+        let mut labels = vec![];
+        if self.show_axes {
+            for (i, axis) in self.axes_values.iter().enumerate() {
+                if let Some(position) = to_screen(self.on_axis(i, axis.end)) {
+                    labels.push((position, axis.name.clone(), AXIS_COLOR.into()));
+                }
+                // Label every tick but the origin, which all axes share
+                for tick in axis.ticks.iter().skip(1) {
+                    let mut point = self.on_axis(i, *tick);
+                    point[(i + 1) % 3] -= 3.0 * TICK_LENGTH;
+                    if let Some(position) = to_screen(point) {
+                        labels.push((position, format_value(*tick), AXIS_COLOR.into()));
+                    }
+                }
+            }
+        }
+        if self.show_constraint_planes {
+            for (i, plane) in self.constraint_planes.iter().enumerate() {
+                if let (false, Some(position)) = (
+                    plane.section.is_empty(),
+                    to_screen(centroid(&plane.section)),
+                ) {
+                    let color = PLANE_COLORS[i % PLANE_COLORS.len()];
+                    labels.push((position, plane.label.clone(), color.into()));
+                }
+            }
+        }
+        if self.show_objective_plane {
+            let section = self.objective_section();
+            if let (false, Some(position)) = (section.is_empty(), to_screen(centroid(&section))) {
+                let label = format!("objective = {:.2}", self.objective_level);
+                labels.push((position, label, OBJECTIVE_COLOR.into()));
+            }
+        }
+
+        let hovered_vertex = pointer.and_then(|pointer| {
+            self.polyhedron
+                .vertices
+                .iter()
+                .filter_map(|v| to_screen(*v).map(|position| (v, position)))
+                .map(|(v, position)| (v, position, position.distance(pointer)))
+                .filter(|(_, _, distance)| *distance <= VERTEX_HOVER_DISTANCE)
+                .min_by(|(_, _, d1), (_, _, d2)| d1.total_cmp(d2))
+        });
+        if let Some((vertex, position, _)) = hovered_vertex {
+            let coordinates = self
+                .axes_values
+                .iter()
+                .zip(self.polyhedron.denormalize(*vertex))
+                .map(|(axis, value)| format!("{} = {}", axis.name, format_value(value)))
+                .join(", ");
+            labels.push((
+                position - egui::vec2(0.0, 16.0),
+                coordinates,
+                VERTEX_COLOR.into(),
+            ));
+        }
+        labels
+    }
+
+    /// Polygon where the iso-plane of the objective cuts the box around the polyhedron
+    fn objective_section(&self) -> Vec<Point> {
+        let (a, c) = self.objective;
+        let (min, max) = self.polyhedron.bounds();
+        plane_section(
+            a,
+            c - self.objective_level,
+            min.map(|v| v - PLANE_MARGIN),
+            max.map(|v| v + PLANE_MARGIN),
+        )
+    }
+
+    /// Sets the points visited by the simplex algorithm, the index of the current step
+    /// and the optimum of the program, all given in the program's coordinates
+    pub fn set_path(&mut self, points: Vec<Vec<f32>>, index: usize, optimum: Option<Vec<f32>>) {
+        self.path_points = points;
+        self.path_index = index;
+        self.optimum_point = optimum;
+    }
+
+    pub fn draw(&mut self, gl: &glow::Context, aspect_ratio: f32, current_point: &[f32]) {
+        let as_vertex = |[x, y, z]: [f32; 3]| [x, y, z, 0.0, 0.0, 0.0];
         unsafe {
-            // create buffer with polyhedron
-            let data = self.vertices.as_slice();
-            let data: &[u8] = from_raw_parts(data.as_ptr().cast(), size_of_val(data));
+            // upload the polyhedron when it changed
+            if self.needs_upload {
+                let edges: Vec<Vertex> = self
+                    .polyhedron
+                    .edges()
+                    .into_iter()
+                    .flat_map(|edge| edge.map(|i| as_vertex(self.polyhedron.vertices[i])))
+                    .collect();
+                let vertices: Vec<Vertex> = self
+                    .polyhedron
+                    .vertices
+                    .iter()
+                    .map(|v| as_vertex(*v))
+                    .collect();
 
-            self.buffer = gl.create_buffer().expect("could not create buffer");
-            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.buffer));
-            gl.buffer_data_size(glow::ARRAY_BUFFER, data.len() as i32, glow::STATIC_DRAW);
-            gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, data, glow::STATIC_DRAW);
+                self.faces
+                    .upload(gl, &self.polyhedron.triangles(), glow::STATIC_DRAW);
+                self.edges.upload(gl, &edges, glow::STATIC_DRAW);
+                self.vertices.upload(gl, &vertices, glow::STATIC_DRAW);
+                let planes: Vec<Vertex> = self
+                    .constraint_planes
+                    .iter()
+                    .flat_map(|plane| fan(&plane.section, plane.a))
+                    .collect();
+                self.planes.upload(gl, &planes, glow::STATIC_DRAW);
+                let (axes, grid) = self.axes_and_grid();
+                self.axes.upload(gl, &axes, glow::STATIC_DRAW);
+                self.grid.upload(gl, &grid, glow::STATIC_DRAW);
+                self.needs_upload = false;
+            }
+            // The path goes through the same centering and scaling as the polyhedron
+            let path: Vec<Vertex> = self
+                .path_points
+                .iter()
+                .map(|p| as_vertex(self.polyhedron.normalize(p)))
+                .collect();
+            let current_step = if self.path_index > 0 {
+                path.get(self.path_index - 1..=self.path_index)
+                    .unwrap_or_default()
+            } else {
+                &[]
+            };
+            let optimum: Vec<Vertex> = self
+                .optimum_point
+                .iter()
+                .map(|p| as_vertex(self.polyhedron.normalize(p)))
+                .collect();
+            self.path.upload(gl, &path, glow::DYNAMIC_DRAW);
+            self.current_step
+                .upload(gl, current_step, glow::DYNAMIC_DRAW);
+            self.optimum.upload(gl, &optimum, glow::DYNAMIC_DRAW);
+            let candidates: Vec<Vertex> = self
+                .candidate_points
+                .iter()
+                .map(|p| as_vertex(self.polyhedron.normalize(p)))
+                .collect();
+            self.candidates.upload(gl, &candidates, glow::DYNAMIC_DRAW);
+            // Paths are shifted apart along the diagonal so that shared edges stay visible
+            let middle = self.comparison_paths.len().saturating_sub(1) as f32 / 2.0;
+            let polyhedron = &self.polyhedron;
+            let comparison: Vec<Vertex> = self
+                .comparison_paths
+                .iter()
+                .enumerate()
+                .flat_map(|(i, (_, points))| {
+                    let offset = (i as f32 - middle) * COMPARISON_OFFSET;
+                    points
+                        .iter()
+                        .map(move |p| as_vertex(polyhedron.normalize(p).map(|v| v + offset)))
+                })
+                .collect();
+            self.comparison.upload(gl, &comparison, glow::DYNAMIC_DRAW);
+            self.current_point.upload(
+                gl,
+                &[as_vertex(self.polyhedron.normalize(current_point))],
+                glow::DYNAMIC_DRAW,
+            );
+
+            let highlight: Vec<Vertex> =
+                match self.hovered.and_then(|i| self.constraint_planes.get(i)) {
+                    Some(plane) => self
+                        .polyhedron
+                        .faces_on_plane(plane.a, plane.c)
+                        .into_iter()
+                        .flat_map(|f| {
+                            let face = self.polyhedron.faces[f];
+                            let [nx, ny, nz] = face.normal;
+                            face.vertices.map(|i| {
+                                let [x, y, z] = self.polyhedron.vertices[i];
+                                [x, y, z, nx, ny, nz]
+                            })
+                        })
+                        .collect(),
+                    None => vec![],
+                };
+            self.highlight.upload(gl, &highlight, glow::DYNAMIC_DRAW);
+            if self.show_objective_plane {
+                let section = self.objective_section();
+                self.objective_plane.upload(
+                    gl,
+                    &fan(&section, self.objective.0),
+                    glow::DYNAMIC_DRAW,
+                );
+            }
 
             gl.use_program(Some(self.rendering_program));
 
-            let projection = glm::ext::perspective(
-                FRAC_PI_4,
-                rect_size[0] as f32 / rect_size[1] as f32,
-                0.01,
-                100.0,
-            );
-            let view = glm::ext::look_at(
-                Vector3::new(0.0, 0.5, 2.0),
-                Vector3::new(0.0, 0.0, -0.01),
-                Vector3::new(0.0, 1.0, 0.0),
-            );
-            let model = glm::ext::rotate(
-                &glm::ext::rotate(
-                    &Matrix4::one(),
-                    self.view_angle.x,
-                    Vector3::new(0.0, 1.0, 0.0),
-                ),
-                self.view_angle.y,
-                Vector3::new(1.0, 0.0, 0.0),
-            );
-            let mvp_mat = projection * view * model;
-
-            let mut mvp = [0.0; 16];
-            for (c, vec) in mvp_mat.as_array().iter().enumerate() {
-                mvp[c] = vec.x;
-                mvp[c + 4] = vec.y;
-                mvp[c + 8] = vec.z;
-                mvp[c + 12] = vec.w
-            }
-            gl.uniform_matrix_4_f32_slice(
-                gl.get_uniform_location(self.rendering_program, "u_mvp")
-                    .as_ref(),
-                true,
-                &mvp,
-            );
+            let (mvp, model) = self.camera.transform(aspect_ratio);
+            self.set_matrix(gl, "u_mvp", &mvp);
+            self.set_matrix(gl, "u_model", &model);
+
+            if !cfg!(target_arch = "wasm32") {
+                // Lets the vertex shader choose the size of points
+                gl.enable(glow::PROGRAM_POINT_SIZE);
+            }
 
             gl.bind_vertex_array(Some(self.vertex_array));
-            gl.enable_vertex_array_attrib(self.vertex_array, 0);
-            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.buffer));
-            gl.vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, 0, 0);
-            gl.draw_arrays(glow::TRIANGLES, 0, self.vertices.len() as i32);
+            gl.enable_vertex_attrib_array(0);
+            gl.enable_vertex_attrib_array(1);
+
+            // The axes and the grid are drawn first, behind everything else
+            if self.show_axes {
+                self.set_style(gl, GRID_COLOR, false, 1.0);
+                self.grid.draw(gl, glow::LINES);
+                self.set_style(gl, AXIS_COLOR, false, 1.0);
+                self.axes.draw(gl, glow::LINES);
+            }
+
+            // Translucent faces are drawn back to front: inner faces first, then outer faces.
+            // Triangles are wound counter-clockwise, so culling selects each side
+            gl.enable(glow::CULL_FACE);
+            gl.front_face(glow::CCW);
+            self.set_style(gl, FACE_COLOR.multiply(self.alpha), true, 1.0);
+            for culled in [glow::FRONT, glow::BACK] {
+                gl.cull_face(culled);
+                self.faces.draw(gl, glow::TRIANGLES);
+            }
+            gl.disable(glow::CULL_FACE);
+            self.set_style(gl, HIGHLIGHT_COLOR.multiply(0.6), true, 1.0);
+            self.highlight.draw(gl, glow::TRIANGLES);
+
+            if self.show_constraint_planes {
+                let mut first = 0;
+                for (i, plane) in self.constraint_planes.iter().enumerate() {
+                    let count = (plane.section.len().saturating_sub(2) * 3) as i32;
+                    let color = PLANE_COLORS[i % PLANE_COLORS.len()];
+                    self.set_style(gl, color.multiply(PLANE_ALPHA), false, 1.0);
+                    self.planes.draw_range(gl, glow::TRIANGLES, first, count);
+                    first += count;
+                }
+            }
+            if self.show_objective_plane {
+                self.set_style(gl, OBJECTIVE_COLOR.multiply(PLANE_ALPHA), false, 1.0);
+                self.objective_plane.draw(gl, glow::TRIANGLES);
+            }
+
+            self.set_style(gl, EDGE_COLOR, false, 1.0);
+            self.edges.draw(gl, glow::LINES);
+            self.set_style(gl, VERTEX_COLOR, false, 6.0);
+            self.vertices.draw(gl, glow::POINTS);
+
+            self.set_style(gl, PATH_COLOR, false, 8.0);
+            self.path.draw(gl, glow::LINE_STRIP);
+            self.path.draw(gl, glow::POINTS);
+            self.set_style(gl, CURRENT_STEP_COLOR, false, 8.0);
+            self.current_step.draw(gl, glow::LINES);
+            let mut first = 0;
+            for (color, points) in &self.comparison_paths {
+                let count = points.len() as i32;
+                self.set_style(gl, Rgba::from(*color), false, 6.0);
+                self.comparison
+                    .draw_range(gl, glow::LINE_STRIP, first, count);
+                self.comparison.draw_range(gl, glow::POINTS, first, count);
+                first += count;
+            }
+            self.set_style(gl, CANDIDATE_COLOR, false, 12.0);
+            self.candidates.draw(gl, glow::POINTS);
+            self.set_style(gl, OPTIMUM_COLOR, false, 20.0);
+            self.optimum.draw(gl, glow::POINTS);
+            self.set_style(gl, CURRENT_POINT_COLOR, false, 14.0);
+            self.current_point.draw(gl, glow::POINTS);
 
             gl.disable_vertex_attrib_array(0);
+            gl.disable_vertex_attrib_array(1);
         }
     }
+
+    unsafe fn set_matrix(&self, gl: &glow::Context, name: &str, matrix: &Matrix4<f32>) {
+        let mut values = [0.0; 16];
+        for (c, vec) in matrix.as_array().iter().enumerate() {
+            values[c] = vec.x;
+            values[c + 4] = vec.y;
+            values[c + 8] = vec.z;
+            values[c + 12] = vec.w
+        }
+        gl.uniform_matrix_4_f32_slice(
+            gl.get_uniform_location(self.rendering_program, name)
+                .as_ref(),
+            true,
+            &values,
+        );
+    }
+
+    unsafe fn set_style(&self, gl: &glow::Context, color: Rgba, lit: bool, point_size: f32) {
+        let location = |name| gl.get_uniform_location(self.rendering_program, name);
+        let [r, g, b, a] = color.to_rgba_unmultiplied();
+        gl.uniform_4_f32(location("u_color").as_ref(), r, g, b, a);
+        gl.uniform_1_i32(location("u_lit").as_ref(), lit as i32);
+        gl.uniform_1_f32(location("u_point_size").as_ref(), point_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticks() {
+        assert_eq!(ticks(400.0), vec![0.0, 100.0, 200.0, 300.0, 400.0]);
+        assert_eq!(ticks(7.0), vec![0.0, 2.0, 4.0, 6.0]);
+        assert_eq!(ticks(0.0), vec![0.0]);
+    }
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value(200.0), "200");
+        assert_eq!(format_value(0.5), "0.5");
+        assert_eq!(format_value(-0.001), "0");
+    }
 }
@@ -0,0 +1,182 @@
+//! Choice of the variables displayed for programs with four variables or more
+use crate::linear_function::{LinearFunction, Variable};
+use crate::LinearProgram;
+use egui::Ui;
+use std::collections::HashMap;
+
+/// What happens to a variable that isn't displayed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hidden {
+    /// The variable is fixed at a value, showing a slice of the polyhedron
+    Fixed(f32),
+    /// The variable is projected out using Fourier–Motzkin elimination
+    Projected,
+}
+
+/// Variables of a program, along with whether they're displayed and how they're hidden
+pub struct Projection {
+    variables: Vec<Variable>,
+    displayed: Vec<bool>,
+    hidden: Vec<Hidden>,
+    /// Largest value of each variable on the polyhedron, used as the range of sliders
+    max_values: Vec<f32>,
+    /// Index in `variables` of each variable of the last displayed program, which may have
+    /// lost some displayed variables along with the constraints eliminated
+    indices: Vec<usize>,
+}
+
+impl Projection {
+    /// Displays the first three variables of a program, projecting out the other ones
+    pub fn new(program: &LinearProgram) -> Projection {
+        let variables = program.non_gap_variables();
+        // Vertices only have coordinates for the variables of the constraints
+        let mut constrained = program.constraints.non_gap_variables();
+        constrained.sort();
+        let mut max_values = vec![0f32; variables.len()];
+        for vertex in program.constraints.vertices() {
+            for (var, value) in constrained.iter().zip(vertex) {
+                if let Some(i) = variables.iter().position(|v| v == var) {
+                    max_values[i] = max_values[i].max(value);
+                }
+            }
+        }
+
+        Projection {
+            displayed: (0..variables.len()).map(|i| i < 3).collect(),
+            hidden: vec![Hidden::Projected; variables.len()],
+            indices: (0..variables.len().min(3)).collect(),
+            variables,
+            max_values,
+        }
+    }
+
+    /// Whether the constraints of the displayed program are those of the original one,
+    /// which is the case unless a variable is projected out
+    pub fn keeps_constraints(&self) -> bool {
+        self.displayed
+            .iter()
+            .zip(&self.hidden)
+            .all(|(displayed, hidden)| *displayed || *hidden != Hidden::Projected)
+    }
+
+    /// Returns the program restricted to the displayed variables
+    pub fn apply(&mut self, program: &LinearProgram) -> LinearProgram {
+        let mut program = program.clone();
+        for ((var, displayed), hidden) in
+            self.variables.iter().zip(&self.displayed).zip(&self.hidden)
+        {
+            if *displayed {
+                continue;
+            }
+            match hidden {
+                Hidden::Fixed(value) => {
+                    program.constraints = program.constraints.fix(var, *value);
+                    program
+                        .linear_function
+                        .replace(var, &LinearFunction::new(*value, HashMap::new()));
+                }
+                Hidden::Projected => {
+                    program.constraints = program.constraints.eliminate(var);
+                    program
+                        .linear_function
+                        .replace(var, &LinearFunction::zero());
+                }
+            }
+        }
+        self.indices = program
+            .non_gap_variables()
+            .iter()
+            .filter_map(|var| self.variables.iter().position(|v| v == var))
+            .collect();
+        program
+    }
+
+    /// Keeps the coordinates of a point that correspond to the variables of the last
+    /// displayed program
+    pub fn point(&self, point: &[f32]) -> Vec<f32> {
+        self.indices
+            .iter()
+            .filter_map(|&i| point.get(i).copied())
+            .collect()
+    }
+
+    /// Shows the controls of the projection, returns true if it changed
+    pub fn ui(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = false;
+        let displayed_count = self.displayed.iter().filter(|d| **d).count();
+
+        egui::Grid::new("projection").show(ui, |ui| {
+            for i in 0..self.variables.len() {
+                // Between two and three variables are displayed at all times
+                let can_toggle = if self.displayed[i] {
+                    displayed_count > 2
+                } else {
+                    displayed_count < 3
+                };
+                ui.add_enabled_ui(can_toggle, |ui| {
+                    changed |= ui
+                        .checkbox(&mut self.displayed[i], &self.variables[i])
+                        .changed();
+                });
+
+                if !self.displayed[i] {
+                    let mut projected = self.hidden[i] == Hidden::Projected;
+                    if ui.checkbox(&mut projected, "projected").changed() {
+                        self.hidden[i] = if projected {
+                            Hidden::Projected
+                        } else {
+                            Hidden::Fixed(0.0)
+                        };
+                        changed = true;
+                    }
+                    if let Hidden::Fixed(value) = &mut self.hidden[i] {
+                        changed |= ui
+                            .add(egui::Slider::new(value, 0.0..=self.max_values[i]))
+                            .changed();
+                    }
+                }
+                ui.end_row();
+            }
+        });
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::Constraints;
+
+    #[test]
+    fn test_apply() {
+        let program = LinearProgram {
+            linear_function: "x + y + z + w".parse().unwrap(),
+            constraints: Constraints::compile("x + y + z + w <= 4\n w <= 2").unwrap(),
+        };
+        let mut projection = Projection::new(&program);
+        assert!(!projection.keeps_constraints());
+        projection.displayed = vec![false, true, true, true];
+        projection.hidden[0] = Hidden::Fixed(1.0);
+
+        let sliced = projection.apply(&program);
+        assert_eq!(sliced.non_gap_variables(), vec!["x", "y", "z"]);
+        assert_eq!(sliced.linear_function.constant, 1.0);
+        assert_eq!(projection.point(&[1.0, 2.0, 3.0, 4.0]), vec![2.0, 3.0, 4.0]);
+        assert!(projection.keeps_constraints());
+    }
+
+    #[test]
+    fn test_point_after_elimination() {
+        // Eliminating a removes the only constraint on b
+        let program = LinearProgram {
+            linear_function: "c + d".parse().unwrap(),
+            constraints: Constraints::compile("b <= a\n c + d <= 2").unwrap(),
+        };
+        let mut projection = Projection::new(&program);
+        projection.displayed = vec![false, true, true, true];
+
+        let projected = projection.apply(&program);
+        assert_eq!(projected.non_gap_variables(), vec!["c", "d"]);
+        assert_eq!(projection.point(&[1.0, 2.0, 3.0, 4.0]), vec![3.0, 4.0]);
+    }
+}
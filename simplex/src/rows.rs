@@ -0,0 +1,312 @@
+//! Rows, bounds and names of a document as the formats of other solvers write them. A row is
+//! a function of the variables compared to a number, possibly within a range
+use crate::constraint::{Constraint, Operator};
+use crate::document::{is_keyword, is_name, Document, NamedConstraint};
+use crate::linear_function::{Coefficient, LinearFunction, Variable, GAP_VARIABLE_IDENTIFIER};
+use std::collections::{HashMap, HashSet};
+
+/// Bounds at least this large are infinite
+pub(crate) const INFINITY: Coefficient = 1e30;
+
+/// Lower and upper bounds of a variable, None being infinite
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub lower: Option<Coefficient>,
+    pub upper: Option<Coefficient>,
+}
+
+impl Default for Bounds {
+    /// Variables are nonnegative
+    fn default() -> Self {
+        Bounds {
+            lower: Some(0.0),
+            upper: None,
+        }
+    }
+}
+
+/// Names of a file or a document along with the names they are written as
+pub(crate) struct Identifiers {
+    /// Start of the names that don't start with a letter
+    prefix: &'static str,
+    /// Characters kept in the names
+    kept: fn(char) -> bool,
+    /// Words that can't be names
+    reserved: fn(&str) -> bool,
+    given: HashMap<String, String>,
+    /// Names already given, which others can't be given
+    taken: HashSet<String>,
+}
+
+impl Identifiers {
+    /// Names read from a file, which documents can parse
+    fn new(prefix: &'static str) -> Self {
+        Identifiers {
+            prefix,
+            kept: char::is_alphanumeric,
+            reserved: is_keyword,
+            given: HashMap::new(),
+            taken: HashSet::new(),
+        }
+    }
+
+    /// Names written for other solvers, made of ASCII letters, digits and underscores
+    fn ascii(prefix: &'static str, reserved: fn(&str) -> bool) -> Self {
+        Identifiers {
+            prefix,
+            kept: |c| c.is_ascii_alphanumeric(),
+            reserved,
+            given: HashMap::new(),
+            taken: HashSet::new(),
+        }
+    }
+
+    /// Returns a name whose characters that aren't kept are replaced: `ε` by `slack`, `⁺`
+    /// and `⁻` by `_pos` and `_neg`, and others by underscores. Reserved words end with one
+    fn base(&self, name: &str) -> String {
+        let mut base = String::new();
+        for c in name.chars() {
+            match c {
+                c if (self.kept)(c) => base.push(c),
+                GAP_VARIABLE_IDENTIFIER => base += "slack",
+                '⁺' => base += "_pos",
+                '⁻' => base += "_neg",
+                _ => base.push('_'),
+            }
+        }
+        if !is_name(&base) {
+            base.insert_str(0, self.prefix);
+        }
+        if (self.reserved)(&base) {
+            base.push('_');
+        }
+        base
+    }
+
+    /// Returns the name a name is written as, `_v` and a number being appended to a name
+    /// already given to another one, so that it isn't read as the index of a variable like `x_1`
+    fn of(&mut self, name: &str) -> String {
+        if let Some(identifier) = self.given.get(name) {
+            return identifier.clone();
+        }
+        let base = self.base(name);
+        let mut identifier = base.clone();
+        for index in 1.. {
+            if !self.taken.contains(&identifier) {
+                break;
+            }
+            identifier = format!("{base}_v{index}");
+        }
+        self.taken.insert(identifier.clone());
+        self.given.insert(name.to_string(), identifier.clone());
+        identifier
+    }
+
+    /// Gives names in a fixed order, the names left as they are first so that they keep them
+    fn give<'a>(&mut self, names: impl Iterator<Item = &'a String>) {
+        let mut names: Vec<&String> = names.collect();
+        names.sort_by_key(|name| (self.base(name) != **name, *name));
+        for name in names {
+            self.of(name);
+        }
+    }
+}
+
+/// Returns a copy of a document whose variables and constraints are renamed, so that other
+/// solvers can read them
+pub(crate) fn exported(document: &Document, reserved: fn(&str) -> bool) -> Document {
+    renamed(
+        document,
+        Identifiers::ascii("R", reserved),
+        Identifiers::ascii("x", reserved),
+    )
+}
+
+/// Returns a copy of a document read from a file whose variables and constraints are renamed,
+/// so that documents can parse them. Variables that don't start with a letter start with a prefix
+pub(crate) fn imported(document: &Document, prefix: &'static str) -> Document {
+    renamed(document, Identifiers::new("R"), Identifiers::new(prefix))
+}
+
+/// Returns a copy of a document whose constraints and variables are given new names, the
+/// names left as they are being given first
+fn renamed(document: &Document, mut names: Identifiers, mut variables: Identifiers) -> Document {
+    names.give(document.constraints.iter().filter_map(|c| c.name.as_ref()));
+    let comparisons = (document.constraints.iter().map(|c| &c.constraint))
+        .chain(&document.bounds)
+        .flat_map(|c| c.left.var_iter().chain(c.right.var_iter()));
+    variables.give(
+        (document.objective.var_iter())
+            .chain(comparisons)
+            .chain(&document.integers)
+            .chain(&document.free),
+    );
+
+    let given = |var: &Variable| variables.given.get(var).cloned().unwrap_or(var.clone());
+    let renamed = |constraint: &Constraint| {
+        let (left, right) = (
+            constraint.left.renamed(given),
+            constraint.right.renamed(given),
+        );
+        Constraint::new(left, constraint.operator, right)
+    };
+    Document {
+        objective: document.objective.renamed(given),
+        constraints: (document.constraints.iter())
+            .map(|c| NamedConstraint {
+                name: c.name.as_ref().map(|name| names.of(name)),
+                constraint: renamed(&c.constraint),
+            })
+            .collect(),
+        bounds: document.bounds.iter().map(renamed).collect(),
+        integers: document.integers.iter().map(given).collect(),
+        free: document.free.iter().map(given).collect(),
+        ..document.clone()
+    }
+}
+
+/// Writes a constraint as a linear function of the variables compared to a number. Strict
+/// comparisons are read as large ones
+pub(crate) fn split(constraint: &Constraint) -> (LinearFunction, Operator, Coefficient) {
+    let mut function = constraint.left.clone() - constraint.right.clone();
+    let rhs = -function.constant;
+    function.constant = 0.0;
+    let operator = match constraint.operator {
+        Operator::Less => Operator::LessEqual,
+        Operator::Greater => Operator::GreaterEqual,
+        operator => operator,
+    };
+    (function, operator, rhs)
+}
+
+/// Returns the operator comparing the same sides in the other order
+pub(crate) fn flipped(operator: Operator) -> Operator {
+    match operator {
+        Operator::LessEqual => Operator::GreaterEqual,
+        Operator::GreaterEqual => Operator::LessEqual,
+        operator => operator,
+    }
+}
+
+/// Row of a program, as in the `ROWS` section of the MPS format or the `Subject To` section
+/// of the LP format, the range giving the values of the function from the right-hand side
+pub(crate) struct Row {
+    pub(crate) name: String,
+    pub(crate) operator: Operator,
+    pub(crate) function: LinearFunction,
+    pub(crate) rhs: Coefficient,
+    pub(crate) range: Option<Coefficient>,
+}
+
+/// Returns the function and the bounds of a range written as two constraints, like
+/// `lo <= x + y` then `x + y <= hi`
+fn range(
+    first: &Constraint,
+    second: &Constraint,
+) -> Option<(LinearFunction, Coefficient, Coefficient)> {
+    let (function, second, b) = split(second);
+    let (mut other, mut first, mut a) = split(first);
+    if other == -function.clone() {
+        (other, first, a) = (function.clone(), flipped(first), -a);
+    }
+    match (first, second) {
+        _ if other != function => None,
+        (Operator::GreaterEqual, Operator::LessEqual) => Some((function, a, b)),
+        (Operator::LessEqual, Operator::GreaterEqual) => Some((function, b, a)),
+        _ => None,
+    }
+}
+
+/// Returns the rows of the constraints of a document, along with a name left for the
+/// objective. Rows get unique names, unnamed constraints being named after their index, and
+/// two constraints of the same name forming a range give a single ranged row
+pub(crate) fn rows(document: &Document) -> (Vec<Row>, String) {
+    let mut used: Vec<String> = document
+        .constraints
+        .iter()
+        .filter_map(|c| c.name.clone())
+        .collect();
+    let mut unused = |base: &str| {
+        let mut name = base.to_string();
+        for index in 1.. {
+            if !used.contains(&name) {
+                break;
+            }
+            name = format!("{base}{index}");
+        }
+        used.push(name.clone());
+        name
+    };
+
+    let mut rows: Vec<Row> = vec![];
+    let mut constraints = document.constraints.iter().peekable();
+    let mut index = 0;
+    while let Some(NamedConstraint { name, constraint }) = constraints.next() {
+        index += 1;
+        let ranged = match (name, constraints.peek()) {
+            (Some(name), Some(next)) if next.name.as_ref() == Some(name) => {
+                range(constraint, &next.constraint)
+            }
+            _ => None,
+        };
+        let name = match name {
+            Some(name) if !rows.iter().any(|row| &row.name == name) => name.clone(),
+            _ => unused(&format!("R{index}")),
+        };
+        let row = match ranged {
+            Some((function, lower, upper)) => {
+                constraints.next();
+                let (operator, range) = match upper == lower {
+                    true => (Operator::Equal, None),
+                    false => (Operator::GreaterEqual, Some(upper - lower)),
+                };
+                let rhs = lower;
+                Row {
+                    name,
+                    operator,
+                    function,
+                    rhs,
+                    range,
+                }
+            }
+            None => {
+                let (function, operator, rhs) = split(constraint);
+                Row {
+                    name,
+                    operator,
+                    function,
+                    rhs,
+                    range: None,
+                }
+            }
+        };
+        rows.push(row);
+    }
+    let objective = unused("OBJ");
+    (rows, objective)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows() {
+        let text = "max: x\nsubject to\n  c: 1 <= x <= 3\n  x >= 1\n  x <= 3\n";
+        let (document, _) = Document::parse(text).unwrap();
+        let (rows, objective) = rows(&document);
+        // Only named constraints form ranges
+        let written: Vec<_> = (rows.iter())
+            .map(|row| (row.name.as_str(), row.operator, row.rhs, row.range))
+            .collect();
+        assert_eq!(
+            written,
+            vec![
+                ("c", Operator::GreaterEqual, 1.0, Some(2.0)),
+                ("R2", Operator::GreaterEqual, 1.0, None),
+                ("R3", Operator::LessEqual, 3.0, None),
+            ]
+        );
+        assert_eq!(objective, "OBJ");
+    }
+}
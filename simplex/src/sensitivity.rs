@@ -0,0 +1,566 @@
+//! What-if analysis: coefficients of the objective and right-hand sides of the constraints
+//! are changed by sliders, and the program is re-solved from its previous optimal basis
+use crate::constraint::{Constraint, Operator};
+use crate::document::{Document, Sense};
+use crate::linear_function::{Coefficient, Variable, GAP_VARIABLE_IDENTIFIER};
+use crate::{rows, LinearProgram, Simplex, SimplexError};
+use egui::{Color32, RichText, Ui};
+use itertools::Itertools;
+
+const CROSSED_COLOR: Color32 = Color32::from_rgb(254, 128, 25);
+
+/// A number of the program that can be changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parameter {
+    /// Coefficient of a variable in the objective, by its index in alphabetical order
+    Objective(usize),
+    /// Right-hand side of a constraint of the document, bounds coming last, by its index
+    Rhs(usize),
+}
+
+/// Solves a program from a basis, usually the optimal basis of a close program.
+/// Starts from scratch if the program isn't feasible anymore at that basis
+pub fn warm_solve(program: &LinearProgram, basis: &[Variable]) -> Result<Simplex, SimplexError> {
+    let warm = program.with_basis(basis);
+    let start = if warm.is_feasible() {
+        warm
+    } else {
+        program.clone()
+    };
+    let mut simplex = Simplex::from(start);
+    loop {
+        match simplex.next_step(true) {
+            Ok(_) => continue,
+            Err(SimplexError::AlreadyOptimal) => return Ok(simplex),
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// Range of values the coefficient of a variable in the objective can take while the basis
+/// of an optimal state stays optimal
+pub fn objective_range(
+    optimal: &LinearProgram,
+    var: &Variable,
+    value: Coefficient,
+) -> (Coefficient, Coefficient) {
+    let (mut min, mut max) = (Coefficient::NEG_INFINITY, Coefficient::INFINITY);
+    let row = optimal
+        .constraints
+        .iter()
+        .find(|c| c.left.name_single_variable().as_ref() == Some(var));
+    match row {
+        // Changing the coefficient by d changes the coefficient of every variable out of base
+        // by d times its coefficient in the row of var, which must stay nonpositive
+        Some(row) => {
+            for other in optimal.out_of_base_variables() {
+                let (reduced_cost, a) = (optimal.linear_function[&other], row.right[&other]);
+                if a > 0.0 {
+                    max = max.min(-reduced_cost / a);
+                } else if a < 0.0 {
+                    min = min.max(-reduced_cost / a);
+                }
+            }
+        }
+        // Out of base, only its own coefficient changes, which must stay nonpositive
+        None => max = -optimal.linear_function[var],
+    }
+    (value + min, value + max)
+}
+
+/// Range of values the right-hand side of a constraint can take while the basis
+/// of an optimal state stays feasible. The constraint is given by the gap variables of its
+/// rows, along with the sign the right-hand side takes in the constant of each row
+pub fn rhs_range(
+    optimal: &LinearProgram,
+    rows: &[(Variable, Coefficient)],
+    value: Coefficient,
+) -> (Coefficient, Coefficient) {
+    let (mut min, mut max) = (Coefficient::NEG_INFINITY, Coefficient::INFINITY);
+    for row in optimal.constraints.iter() {
+        let constant = row.right.constant;
+        // A basic gap variable moves along with the constant of its row, and every other
+        // basic variable by -d times its coefficient on the gap variable
+        let rate: Coefficient = rows
+            .iter()
+            .map(|(gap_variable, sign)| {
+                if row.left.name_single_variable().as_ref() == Some(gap_variable) {
+                    *sign
+                } else {
+                    -sign * row.right[gap_variable]
+                }
+            })
+            .sum();
+        if rate > 0.0 {
+            min = min.max(-constant / rate);
+        } else if rate < 0.0 {
+            max = max.min(constant / -rate);
+        }
+    }
+    (value + min, value + max)
+}
+
+/// Shadow price of every row of a program, from one of its optimal states: how much the
+/// objective improves when the row is loosened by one unit. Rows whose gap variable is basic
+/// aren't tight, and are worth nothing
+pub fn shadow_prices(optimal: &LinearProgram) -> Vec<(Variable, Coefficient)> {
+    (0..optimal.constraints.gap_variables_count())
+        .map(|i| {
+            let gap_variable = format!("{GAP_VARIABLE_IDENTIFIER}{i}");
+            let price = 0.0 - optimal.linear_function[&gap_variable];
+            (gap_variable, price)
+        })
+        .collect()
+}
+
+/// Bound on the objective proven by shadow prices, which form a solution of the dual.
+/// The row i of the compiled program reads εi = bi + ai·x, so the objective can't exceed its
+/// constant plus the sum of the prices times the bi. At the optimum, the bound is reached
+pub fn dual_bound(program: &LinearProgram, prices: &[(Variable, Coefficient)]) -> Coefficient {
+    let weighted: Coefficient = (program.constraints.iter())
+        .zip(prices)
+        .map(|(row, (_, price))| price * row.right.constant)
+        .sum();
+    program.linear_function.constant + weighted
+}
+
+/// Sliders on the numbers of a program, which is re-solved whenever one of them moves
+pub struct WhatIf {
+    /// Document the program is compiled from, whose right-hand sides are replaced by the
+    /// values of the sliders
+    document: Document,
+    /// Program as compiled, whose objective is replaced by the values of the sliders
+    program: LinearProgram,
+    /// -1 when minimizing, the sliders showing the objective as written
+    sign: Coefficient,
+    variables: Vec<Variable>,
+    /// Basic variable of each constraint of the compiled program
+    gap_variables: Vec<Variable>,
+    objective: Vec<Coefficient>,
+    /// Right-hand side of each constraint of the document, then of each bound, as written
+    rhs: Vec<Coefficient>,
+    /// Name of each constraint of the document, or the constraint itself
+    rhs_names: Vec<String>,
+    /// Gap variables of the rows compiled from each constraint of the document, with the sign
+    /// its right-hand side takes in their constant. An equality gives two opposite rows
+    rhs_rows: Vec<Vec<(Variable, Coefficient)>>,
+    /// Range of each slider, around the compiled value
+    objective_spans: Vec<Coefficient>,
+    rhs_spans: Vec<Coefficient>,
+    /// Last solve, warm-started from the previous optimal basis and ending at the optimal
+    /// state with the current values. None if the program is unbounded
+    solved: Option<Simplex>,
+    /// Pivots made by the last re-solve
+    pivots: usize,
+    /// Variables that entered and left the basis at the last re-solve
+    basis_change: Option<(Vec<Variable>, Vec<Variable>)>,
+    /// Parameter that left its sensitivity range at the last re-solve
+    crossed: Option<Parameter>,
+}
+
+/// Range of a slider around a value
+fn span(value: Coefficient) -> Coefficient {
+    2.0 * value.abs().max(1.0)
+}
+
+/// Constraints of a document, then its bounds, in the order they are compiled
+fn sources(document: &Document) -> impl Iterator<Item = &Constraint> {
+    (document.constraints.iter())
+        .map(|c| &c.constraint)
+        .chain(&document.bounds)
+}
+
+impl WhatIf {
+    /// Starts from a document, solving its program from scratch
+    pub fn new(document: &Document) -> WhatIf {
+        let sign = match document.sense {
+            Sense::Maximize => 1.0,
+            Sense::Minimize => -1.0,
+        };
+        let (program, row_items) = document.program();
+        let variables = program.non_gap_variables();
+        let objective: Vec<Coefficient> = variables
+            .iter()
+            .map(|var| sign * program.linear_function[var])
+            .collect();
+
+        // Rows of a constraint read εi = rhs - a·x when it is <=, and the opposite when it is >=
+        let gap_variables = program.basis();
+        let mut rhs = vec![];
+        let mut rhs_names = vec![];
+        let mut rhs_rows = vec![];
+        let names = (document.constraints.iter().map(|c| c.name.clone()))
+            .chain(document.bounds.iter().map(|_| None));
+        for (item, (constraint, name)) in sources(document).zip(names).enumerate() {
+            let (_, operator, value) = rows::split(constraint);
+            let signs: &[Coefficient] = match operator {
+                Operator::GreaterEqual => &[-1.0],
+                Operator::Equal => &[1.0, -1.0],
+                _ => &[1.0],
+            };
+            let rows = (row_items.iter().zip(&gap_variables))
+                .filter(|(row_item, _)| **row_item == item)
+                .zip(signs)
+                .map(|((_, gap_variable), sign)| (gap_variable.clone(), *sign))
+                .collect();
+            rhs.push(value);
+            rhs_names.push(name.unwrap_or_else(|| constraint.to_string()));
+            rhs_rows.push(rows);
+        }
+        let solved = warm_solve(&program, &[]).ok();
+
+        WhatIf {
+            document: document.clone(),
+            program,
+            sign,
+            gap_variables,
+            objective_spans: objective.iter().copied().map(span).collect(),
+            rhs_spans: rhs.iter().copied().map(span).collect(),
+            variables,
+            objective,
+            rhs,
+            rhs_names,
+            rhs_rows,
+            solved,
+            pivots: 0,
+            basis_change: None,
+            crossed: None,
+        }
+    }
+
+    /// Returns the compiled program with the values of the sliders, the right-hand sides
+    /// being written in the document before compiling it again
+    pub fn program(&self) -> LinearProgram {
+        let mut document = self.document.clone();
+        let sources = (document.constraints.iter_mut())
+            .map(|c| &mut c.constraint)
+            .chain(&mut document.bounds);
+        for (constraint, value) in sources.zip(&self.rhs) {
+            let (_, _, rhs) = rows::split(constraint);
+            constraint.right.constant += value - rhs;
+        }
+        let (mut program, _) = document.program();
+        for (var, value) in self.variables.iter().zip(&self.objective) {
+            program.linear_function[var] = self.sign * value;
+        }
+        program
+    }
+
+    /// Returns the last solve, from the previous optimal basis to the optimal state with the
+    /// current values. None if the program is unbounded
+    pub fn simplex(&self) -> Option<&Simplex> {
+        self.solved.as_ref()
+    }
+
+    /// Returns the optimal state with the current values, None if unbounded
+    fn optimal(&self) -> Option<&LinearProgram> {
+        self.solved.as_ref().map(Simplex::current_state)
+    }
+
+    /// Returns the optimal point with the current values, None if unbounded
+    pub fn optimum(&self) -> Option<Vec<f32>> {
+        self.optimal().map(|optimal| optimal.point())
+    }
+
+    /// Returns the range of a parameter in which the current basis stays optimal,
+    /// None if the program is unbounded
+    pub fn range(&self, parameter: Parameter) -> Option<(Coefficient, Coefficient)> {
+        let value = match parameter {
+            Parameter::Objective(i) => self.objective[i],
+            Parameter::Rhs(i) => self.rhs[i],
+        };
+        self.range_around(parameter, value)
+    }
+
+    /// Returns the range of a parameter in which the current basis stays optimal,
+    /// the current optimal state being the one for the given value of the parameter
+    fn range_around(
+        &self,
+        parameter: Parameter,
+        value: Coefficient,
+    ) -> Option<(Coefficient, Coefficient)> {
+        let optimal = self.optimal()?;
+        match parameter {
+            Parameter::Objective(i) => {
+                let (min, max) = objective_range(optimal, &self.variables[i], self.sign * value);
+                // Minimizing flips the range of the objective as written
+                Some(if self.sign > 0.0 {
+                    (min, max)
+                } else {
+                    (-max, -min)
+                })
+            }
+            Parameter::Rhs(i) => Some(rhs_range(optimal, &self.rhs_rows[i], value)),
+        }
+    }
+
+    /// Re-solves the program after a parameter changed, from the previous optimal basis
+    fn resolve(&mut self, parameter: Parameter, previous: Coefficient) {
+        let value = match parameter {
+            Parameter::Objective(i) => self.objective[i],
+            Parameter::Rhs(i) => self.rhs[i],
+        };
+        let previous_basis = self
+            .optimal()
+            .map(|optimal| optimal.basis())
+            .unwrap_or_default();
+        // The optimal state still is the one of the previous value
+        let range = self.range_around(parameter, previous);
+        self.crossed = range
+            .filter(|(min, max)| {
+                (*min..=*max).contains(&previous) && !(*min..=*max).contains(&value)
+            })
+            .map(|_| parameter);
+
+        match warm_solve(&self.program(), &previous_basis) {
+            Ok(simplex) => {
+                self.pivots = simplex.historic().len() - 1;
+                let basis = simplex.current_state().basis();
+                let entered: Vec<Variable> = basis
+                    .iter()
+                    .filter(|var| !previous_basis.contains(var))
+                    .cloned()
+                    .collect();
+                let left: Vec<Variable> = previous_basis
+                    .iter()
+                    .filter(|var| !basis.contains(var))
+                    .cloned()
+                    .collect();
+                self.basis_change = (!entered.is_empty()).then_some((entered, left));
+                self.solved = Some(simplex);
+            }
+            Err(_) => {
+                self.pivots = 0;
+                self.basis_change = None;
+                self.solved = None;
+            }
+        }
+    }
+
+    /// Name of a row, given by the user or the one of its gap variable
+    fn row_name(&self, i: usize) -> String {
+        let gap_variable = &self.gap_variables[i];
+        let name = self.program.constraints.name(gap_variable);
+        name.unwrap_or(gap_variable).clone()
+    }
+
+    fn display_name(&self, var: &Variable) -> String {
+        self.program.constraints.display_name(var)
+    }
+
+    /// Proof that the optimum can't be beaten, as the bound given by the shadow prices
+    /// of the tight rows
+    fn certificate(&self, prices: &[(Variable, Coefficient)]) -> String {
+        let program = self.program();
+        let terms = (program.constraints.iter().zip(prices).enumerate())
+            .filter(|(_, (_, (_, price)))| *price != 0.0)
+            .map(|(i, (row, (_, price)))| {
+                format!("{price:.2} × {} ({})", row.right.constant, self.row_name(i))
+            })
+            .join(" + ");
+        let terms = if terms.is_empty() {
+            "0".to_string()
+        } else {
+            terms
+        };
+        let bound = self.sign * dual_bound(&program, prices);
+        let comparison = if self.sign > 0.0 { "≤" } else { "≥" };
+        format!("Certificate: objective {comparison} {bound:.2}, as priced by {terms}")
+    }
+
+    /// Shows a slider for every coefficient of the objective and every right-hand side,
+    /// along with the range in which the basis stays optimal. Returns true if a value changed
+    pub fn ui(&mut self, ui: &mut Ui) -> bool {
+        let mut changed = None;
+        let parameters = (0..self.variables.len())
+            .map(Parameter::Objective)
+            .chain((0..self.rhs.len()).map(Parameter::Rhs))
+            .collect::<Vec<_>>();
+
+        let prices = self.optimal().map(shadow_prices);
+        egui::Grid::new("what if").show(ui, |ui| {
+            for parameter in parameters {
+                let name = match parameter {
+                    Parameter::Objective(i) => format!("objective {}", self.variables[i]),
+                    Parameter::Rhs(i) => format!("rhs {}", self.rhs_names[i]),
+                };
+                let color = if self.crossed == Some(parameter) {
+                    CROSSED_COLOR
+                } else {
+                    ui.visuals().text_color()
+                };
+                ui.label(RichText::new(name).color(color));
+
+                let range = self.range(parameter);
+                let (value, span) = match parameter {
+                    Parameter::Objective(i) => (&mut self.objective[i], self.objective_spans[i]),
+                    Parameter::Rhs(i) => (&mut self.rhs[i], self.rhs_spans[i]),
+                };
+                let previous = *value;
+                let values = match parameter {
+                    // The constant of a row keeps its sign, as a negative one would make the
+                    // origin infeasible
+                    Parameter::Rhs(i) => match self.rhs_rows[i][..] {
+                        [(_, sign)] if (sign * previous >= 0.0) == (sign > 0.0) => 0.0..=span,
+                        [_] => -span..=0.0,
+                        _ => -span..=span,
+                    },
+                    Parameter::Objective(_) => -span..=span,
+                };
+                let slider = egui::Slider::new(value, values);
+                if ui.add(slider).changed() {
+                    changed = Some((parameter, previous));
+                }
+                ui.label(match range {
+                    Some((min, max)) => format!("[{min:.2}, {max:.2}]"),
+                    None => "-".to_string(),
+                });
+                if let (Parameter::Rhs(i), Some(prices)) = (parameter, &prices) {
+                    // Each row of the constraint moves by the sign of the right-hand side
+                    let price: Coefficient = (self.rhs_rows[i].iter())
+                        .filter_map(|(gap_variable, sign)| {
+                            let (_, price) = prices.iter().find(|(var, _)| var == gap_variable)?;
+                            Some(sign * price)
+                        })
+                        .sum();
+                    ui.label(format!("price {price:.2}"));
+                }
+                ui.end_row();
+            }
+        });
+
+        match (self.optimal(), &self.basis_change) {
+            (None, _) => ui.colored_label(Color32::RED, "This program is unbounded"),
+            (Some(_), Some((entered, left))) => ui.colored_label(
+                CROSSED_COLOR,
+                format!(
+                    "The basis changed: {} entered, {} left ({} pivots)",
+                    entered.iter().map(|var| self.display_name(var)).join(", "),
+                    left.iter().map(|var| self.display_name(var)).join(", "),
+                    self.pivots
+                ),
+            ),
+            (Some(_), None) => {
+                ui.label(format!("The basis stayed optimal ({} pivots)", self.pivots))
+            }
+        };
+
+        if let Some(prices) = &prices {
+            ui.label(self.certificate(prices));
+        }
+
+        if let Some((parameter, previous)) = changed {
+            self.resolve(parameter, previous);
+        }
+        changed.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::Constraints;
+
+    fn program() -> LinearProgram {
+        LinearProgram {
+            linear_function: "x + 2y".parse().unwrap(),
+            constraints: Constraints::compile("x + y <= 4\n y <= 3").unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_sensitivity_ranges() {
+        let optimal = warm_solve(&program(), &[]).unwrap();
+        let optimal = optimal.current_state();
+        assert_eq!(optimal.point(), vec![1.0, 3.0]);
+
+        assert_eq!(objective_range(optimal, &"x".to_string(), 1.0), (0.0, 2.0));
+        assert_eq!(
+            objective_range(optimal, &"y".to_string(), 2.0),
+            (1.0, Coefficient::INFINITY)
+        );
+        assert_eq!(
+            rhs_range(optimal, &[("ε0".to_string(), 1.0)], 4.0),
+            (3.0, Coefficient::INFINITY)
+        );
+        assert_eq!(
+            rhs_range(optimal, &[("ε1".to_string(), 1.0)], 3.0),
+            (0.0, 4.0)
+        );
+    }
+
+    #[test]
+    fn test_shadow_prices() {
+        let text = "min: -x - 2y\ncapacity: x + y <= 4\nlimit: y <= 3";
+        let program: LinearProgram = text.parse().unwrap();
+        let optimal = warm_solve(&program, &[]).unwrap();
+        let optimal = optimal.current_state();
+        let prices = shadow_prices(optimal);
+        assert_eq!(
+            prices,
+            vec![("ε0".to_string(), 1.0), ("ε1".to_string(), 1.0)]
+        );
+        assert_eq!(dual_bound(&program, &prices), optimal.objective_value());
+
+        // Names are carried through the pivots
+        let named = optimal.named().to_string();
+        assert!(named.contains("slack(capacity)") && named.contains("slack(limit)"));
+        let (document, _) = Document::parse(text).unwrap();
+        let what_if = WhatIf::new(&document);
+        assert_eq!(what_if.row_name(1), "limit");
+        assert_eq!(
+            what_if.certificate(&prices),
+            "Certificate: objective ≥ -7.00, as priced by 1.00 × 4 (capacity) + 1.00 × 3 (limit)"
+        );
+    }
+
+    #[test]
+    fn test_warm_solve() {
+        let optimal = warm_solve(&program(), &[]).unwrap();
+        let basis = optimal.current_state().basis();
+
+        // Within its range, a right-hand side keeps the basis, which is optimal right away
+        let (document, _) = Document::parse("max: x + 2y\nx + y <= 4\ny <= 3").unwrap();
+        let mut what_if = WhatIf::new(&document);
+        what_if.rhs[0] = 5.0;
+        what_if.resolve(Parameter::Rhs(0), 4.0);
+        assert_eq!(what_if.pivots, 0);
+        assert_eq!(what_if.crossed, None);
+        // The path shown starts from the previous optimal basis
+        let simplex = what_if.simplex().unwrap();
+        assert_eq!(simplex.historic().len(), 1);
+        assert_eq!(simplex.initial_state().basis(), basis);
+        assert_eq!(what_if.optimum(), Some(vec![2.0, 3.0]));
+
+        // Beyond it, the basis changes
+        what_if.objective[0] = 3.0;
+        what_if.resolve(Parameter::Objective(0), 1.0);
+        assert_eq!(what_if.crossed, Some(Parameter::Objective(0)));
+        assert!(what_if.basis_change.is_some());
+        assert_eq!(what_if.optimum(), Some(vec![5.0, 0.0]));
+        assert_ne!(what_if.optimal().unwrap().basis(), basis);
+    }
+
+    #[test]
+    fn test_rhs_of_constraints() {
+        let text = "max: x + y\nbalance: x - y = 1\nx + y <= 4\nfloor: y >= 1";
+        let (document, _) = Document::parse(text).unwrap();
+        let mut what_if = WhatIf::new(&document);
+        // The equality has a single slider, and the one of floor reads as written
+        assert_eq!(what_if.rhs, vec![1.0, 4.0, 1.0]);
+        assert_eq!(what_if.rhs_names, vec!["balance", "x + y <= 4", "floor"]);
+        assert_eq!(what_if.optimum(), Some(vec![2.5, 1.5]));
+
+        // Moving it moves both rows of the equality, which still holds
+        what_if.rhs[0] = 2.0;
+        what_if.resolve(Parameter::Rhs(0), 1.0);
+        assert_eq!(what_if.optimum(), Some(vec![3.0, 1.0]));
+        assert_eq!(what_if.range(Parameter::Rhs(0)), Some((-4.0, 2.0)));
+
+        // The floor of y is a >= row, whose range is given as written
+        assert_eq!(
+            what_if.range(Parameter::Rhs(2)),
+            Some((Coefficient::NEG_INFINITY, 1.0))
+        );
+    }
+}
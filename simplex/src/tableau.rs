@@ -0,0 +1,189 @@
+//! Classic tableau representation of a state of the algorithm
+use crate::linear_function::{Coefficient, Variable, GAP_VARIABLE_IDENTIFIER};
+use crate::LinearProgram;
+use egui::{Color32, RichText, Ui};
+use itertools::Itertools;
+
+const PIVOT_COLOR: Color32 = Color32::from_rgb(215, 153, 33);
+const PIVOT_LINE_COLOR: Color32 = Color32::from_rgb(80, 60, 30);
+
+/// The tableau of a dictionary: each row reads basic + sum(coefficient * variable) = rhs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tableau {
+    /// Every variable of the program, gap variables last, under the name of their row
+    pub variables: Vec<Variable>,
+    pub basis: Vec<Variable>,
+    pub rows: Vec<Vec<Coefficient>>,
+    pub rhs: Vec<Coefficient>,
+    /// Coefficients of the objective, the pivot rule picks a positive one to enter the basis
+    pub reduced_costs: Vec<Coefficient>,
+    pub objective_value: Coefficient,
+    /// Index of the variable entering the basis at the next step, if not optimal
+    pub entering: Option<usize>,
+    /// Index of the row leaving the basis at the next step
+    pub leaving: Option<usize>,
+    /// Ratio test on the entering variable
+    pub ratios: Vec<Option<Coefficient>>,
+}
+
+/// Sorts gap variables after the other ones, by their index
+fn variable_order(var: &Variable) -> (bool, usize, Variable) {
+    match var.strip_prefix(GAP_VARIABLE_IDENTIFIER) {
+        Some(index) => (true, index.parse().unwrap_or(usize::MAX), var.clone()),
+        None => (false, 0, var.clone()),
+    }
+}
+
+impl Tableau {
+    /// Builds the tableau of a state, along with the pivot chosen by Bland's rule
+    pub fn new(program: &LinearProgram) -> Tableau {
+        let basis = program.basis();
+        let variables: Vec<Variable> = basis
+            .iter()
+            .cloned()
+            .chain(program.out_of_base_variables())
+            .chain(program.linear_function.var_iter().cloned())
+            .unique()
+            .sorted_by_key(variable_order)
+            .collect();
+
+        // The dictionary row basic = c + a.x becomes basic - a.x = c
+        let rows = program
+            .constraints
+            .iter()
+            .map(|constraint| {
+                variables
+                    .iter()
+                    .map(|var| {
+                        if constraint.left.contains(var) {
+                            1.0
+                        } else if constraint.right.contains(var) {
+                            -constraint.right[var]
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let rhs = program
+            .constraints
+            .iter()
+            .map(|c| c.right.constant)
+            .collect();
+        let reduced_costs = variables
+            .iter()
+            .map(|var| program.linear_function[var])
+            .collect();
+
+        let entering_variable = program.linear_function.first_positive_coefficient(true);
+        let entering = entering_variable
+            .as_ref()
+            .and_then(|var| variables.iter().position(|v| v == var));
+        let leaving = entering_variable
+            .as_ref()
+            .and_then(|var| program.constraints.most_restrictive(var));
+        let ratios = match &entering_variable {
+            Some(var) => program.constraints.ratios(var),
+            None => vec![None; program.constraints.iter().count()],
+        };
+
+        // Gap variables of named rows are shown as slack(name)
+        let name = |var: &Variable| program.constraints.display_name(var);
+        // Rows whose left side isn't a single variable have no basic variable
+        let row_basis = program
+            .constraints
+            .iter()
+            .map(|c| match c.left.name_single_variable() {
+                Some(var) => name(&var),
+                None => "-".to_string(),
+            })
+            .collect();
+        Tableau {
+            variables: variables.iter().map(name).collect(),
+            basis: row_basis,
+            rows,
+            rhs,
+            reduced_costs,
+            objective_value: program.objective_value(),
+            entering,
+            leaving,
+            ratios,
+        }
+    }
+
+    /// Draws the tableau, highlighting the entering column, the leaving row and the pivot
+    pub fn draw(&self, ui: &mut Ui) {
+        let cell = |text: String, column: Option<usize>, row: Option<usize>| {
+            let in_column = column.is_some() && column == self.entering;
+            let in_row = row.is_some() && row == self.leaving;
+            let text = RichText::new(text).monospace();
+            match (in_column, in_row) {
+                (true, true) => text.background_color(PIVOT_COLOR).color(Color32::BLACK),
+                (true, false) | (false, true) => text.background_color(PIVOT_LINE_COLOR),
+                _ => text,
+            }
+        };
+
+        egui::Grid::new("tableau")
+            .striped(true)
+            .spacing([12.0, 4.0])
+            .show(ui, |ui| {
+                ui.label(cell("basis".to_string(), None, None));
+                for (j, var) in self.variables.iter().enumerate() {
+                    ui.label(cell(var.clone(), Some(j), None));
+                }
+                ui.label(cell("RHS".to_string(), None, None));
+                ui.label(cell("ratio".to_string(), None, None));
+                ui.end_row();
+
+                for (i, row) in self.rows.iter().enumerate() {
+                    let basic = self.basis.get(i).map_or("-", String::as_str);
+                    ui.label(cell(basic.to_string(), None, Some(i)));
+                    for (j, value) in row.iter().enumerate() {
+                        ui.label(cell(format!("{value:.2}"), Some(j), Some(i)));
+                    }
+                    ui.label(cell(format!("{:.2}", self.rhs[i]), None, Some(i)));
+                    let ratio = match self.ratios.get(i).copied().flatten() {
+                        Some(ratio) => format!("{ratio:.2}"),
+                        None => "-".to_string(),
+                    };
+                    ui.label(cell(ratio, None, Some(i)));
+                    ui.end_row();
+                }
+
+                ui.label(cell("z".to_string(), None, None));
+                for (j, value) in self.reduced_costs.iter().enumerate() {
+                    ui.label(cell(format!("{value:.2}"), Some(j), None));
+                }
+                ui.label(cell(format!("{:.2}", self.objective_value), None, None));
+                ui.end_row();
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::Constraints;
+
+    #[test]
+    fn test_tableau() {
+        let lp = LinearProgram {
+            linear_function: "x + 2y".parse().unwrap(),
+            constraints: Constraints::compile("x + y <= 4\n y <= 3").unwrap(),
+        };
+        let tableau = Tableau::new(&lp);
+
+        assert_eq!(tableau.variables, vec!["x", "y", "ε0", "ε1"]);
+        assert_eq!(tableau.basis, vec!["ε0", "ε1"]);
+        assert_eq!(tableau.rows[0], vec![1.0, 1.0, 1.0, 0.0]);
+        assert_eq!(tableau.rhs, vec![4.0, 3.0]);
+        assert_eq!(tableau.reduced_costs, vec![1.0, 2.0, 0.0, 0.0]);
+
+        // Bland's rule picks x, which the first constraint limits
+        assert_eq!(tableau.entering, Some(0));
+        assert_eq!(tableau.leaving, Some(0));
+        assert_eq!(tableau.ratios, vec![Some(4.0), None]);
+    }
+}
@@ -0,0 +1,174 @@
+//! Terminal-like output of the steps of the algorithm, for programs with four variables or more
+use crate::format::Format;
+use crate::{LinearProgram, Simplex};
+use egui::{Color32, FontId, RichText, Ui};
+
+const HEADING_COLOR: Color32 = Color32::from_rgb(242, 153, 51);
+const REMOVED_COLOR: Color32 = Color32::from_rgb(204, 36, 29);
+const ADDED_COLOR: Color32 = Color32::from_rgb(152, 151, 26);
+const TEXT_COLOR: Color32 = Color32::LIGHT_GRAY;
+
+/// A line printed in the terminal
+#[derive(Debug, Clone, PartialEq)]
+enum Line {
+    Heading(String),
+    Text(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Describes how the algorithm went from a state to the next one
+fn step_lines(previous: Option<&LinearProgram>, current: &LinearProgram) -> Vec<Line> {
+    let mut lines = vec![];
+
+    if let Some(pivot) = previous.and_then(|p| p.pivot_to(current)) {
+        let previous = previous.unwrap();
+        let name = |var: &String| previous.constraints.display_name(var);
+        lines.push(Line::Text(format!(
+            "entering: {}    leaving: {}",
+            name(&pivot.entering),
+            name(&pivot.leaving)
+        )));
+
+        // Ratio test that chose the leaving variable
+        lines.push(Line::Text(format!(
+            "ratio test on {}:",
+            name(&pivot.entering)
+        )));
+        let ratios = previous.constraints.ratios(&pivot.entering);
+        for (constraint, ratio) in previous.constraints.iter().zip(ratios) {
+            let basic = constraint.left.to_string();
+            let marker = if basic == pivot.leaving {
+                "  <- leaves"
+            } else {
+                ""
+            };
+            let basic = name(&basic);
+            lines.push(Line::Text(match ratio {
+                Some(ratio) => format!(
+                    "  {basic:<8} {:>10.2} / {:<8.2} = {ratio:.2}{marker}",
+                    constraint.right.constant, -constraint.right[&pivot.entering],
+                ),
+                None => format!("  {basic:<8} {:>10}", "-"),
+            }));
+        }
+    }
+
+    let point = current
+        .values()
+        .iter()
+        .map(|(v, c)| format!("{v} = {c:.2}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    lines.push(Line::Text(format!("point: ({point})")));
+    lines.push(Line::Text(format!(
+        "objective: {:.2}",
+        current.objective_value()
+    )));
+
+    // Dictionary, as a diff from the previous one
+    lines.push(Line::Text("dictionary:".to_string()));
+    let dictionary = |program: &LinearProgram| {
+        let program = program.named();
+        let format = Format {
+            precision: Some(2),
+            ..Format::default()
+        };
+        let mut rows = vec![format!("max {}", format.function(&program.linear_function))];
+        rows.extend(program.constraints.iter().map(|c| {
+            let sides = [c.left.clone(), c.right.clone()];
+            format.comparison(&sides, &[c.operator])
+        }));
+        rows
+    };
+    let current_rows = dictionary(current);
+    let previous_rows = previous.map(dictionary).unwrap_or_default();
+    for (i, row) in current_rows.into_iter().enumerate() {
+        match previous_rows.get(i) {
+            Some(previous_row) if *previous_row != row => {
+                lines.push(Line::Removed(format!("- {previous_row}")));
+                lines.push(Line::Added(format!("+ {row}")));
+            }
+            _ => lines.push(Line::Text(format!("  {row}"))),
+        }
+    }
+
+    lines
+}
+
+/// Prints every step of the algorithm up to the current one
+pub fn draw_steps(ui: &mut Ui, simplex: &Simplex) {
+    let historic = &simplex.historic()[..=simplex.index()];
+    egui::ScrollArea::vertical()
+        .auto_shrink([false; 2])
+        .stick_to_bottom(true)
+        .show(ui, |ui| {
+            for (i, current) in historic.iter().enumerate() {
+                let previous = i.checked_sub(1).map(|i| &historic[i]);
+                let heading = if i == 0 {
+                    "── initial dictionary ──".to_string()
+                } else {
+                    format!("── step {i} ──")
+                };
+
+                for line in [Line::Heading(heading)]
+                    .into_iter()
+                    .chain(step_lines(previous, current))
+                {
+                    let (text, color) = match line {
+                        Line::Heading(text) => (text, HEADING_COLOR),
+                        Line::Text(text) => (text, TEXT_COLOR),
+                        Line::Removed(text) => (text, REMOVED_COLOR),
+                        Line::Added(text) => (text, ADDED_COLOR),
+                    };
+                    ui.label(
+                        RichText::new(text)
+                            .font(FontId::monospace(14.0))
+                            .color(color),
+                    );
+                }
+                ui.add_space(8.0);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraint::Constraints;
+    use crate::linear_function::LinearFunction;
+
+    #[test]
+    fn test_step_lines() {
+        let lp = LinearProgram {
+            linear_function: "x + y + z + w".parse::<LinearFunction>().unwrap(),
+            constraints: Constraints::compile("x <= 2\n y <= 3\n z + w <= 4").unwrap(),
+        };
+        let mut simplex = Simplex::from(lp);
+        simplex.next_step(true).unwrap();
+        let historic = simplex.historic();
+        let lines = step_lines(Some(&historic[0]), &historic[1]);
+
+        assert_eq!(
+            lines[0],
+            Line::Text("entering: w    leaving: ε2".to_string())
+        );
+        assert!(lines.contains(&Line::Text("objective: 4.00".to_string())));
+        assert!(lines.contains(&Line::Added("+ w = 4 - z - ε2".to_string())));
+    }
+
+    #[test]
+    fn test_dictionary_rounded() {
+        let lp = LinearProgram {
+            linear_function: "x + y + z + w".parse::<LinearFunction>().unwrap(),
+            constraints: Constraints::compile("3w <= 1\n y <= 3\n z + x <= 4").unwrap(),
+        };
+        let mut simplex = Simplex::from(lp);
+        simplex.next_step(true).unwrap();
+        let historic = simplex.historic();
+        let lines = step_lines(Some(&historic[0]), &historic[1]);
+
+        assert!(lines.contains(&Line::Added("+ w = 0.33 - 0.33ε0".to_string())));
+        assert!(lines.contains(&Line::Added("+ max 0.33 + x + y + z - 0.33ε0".to_string())));
+    }
+}